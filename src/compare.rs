@@ -0,0 +1,214 @@
+use lazy_static::lazy_static;
+use publicsuffix::{List, Psl};
+use url::{Position, Url};
+
+use crate::{normalize_and_parse_url, NormalizeUrlError, Options};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// A component of a URL.
+pub enum UrlComponent {
+    /// The scheme, e.g. `https`.
+    Scheme,
+    /// The username.
+    Username,
+    /// The password.
+    Password,
+    /// The host.
+    Host,
+    /// The explicit port.
+    Port,
+    /// The path. An empty path and `/` are equal.
+    Path,
+    /// The query.
+    Query,
+    /// The fragment.
+    Fragment,
+}
+
+impl UrlComponent {
+    const ALL: [UrlComponent; 8] = [
+        UrlComponent::Scheme,
+        UrlComponent::Username,
+        UrlComponent::Password,
+        UrlComponent::Host,
+        UrlComponent::Port,
+        UrlComponent::Path,
+        UrlComponent::Query,
+        UrlComponent::Fragment,
+    ];
+
+    fn value(self, url_obj: &Url) -> Option<&str> {
+        match self {
+            UrlComponent::Scheme => Some(url_obj.scheme()),
+            UrlComponent::Username => {
+                Some(url_obj.username()).filter(|username| !username.is_empty())
+            }
+            UrlComponent::Password => url_obj.password(),
+            UrlComponent::Host => url_obj.host_str(),
+            UrlComponent::Port => Some(&url_obj[Position::AfterHost..Position::AfterPort])
+                .map(|port| port.trim_start_matches(':'))
+                .filter(|port| !port.is_empty()),
+            // `/` is the empty path of hierarchical URLs
+            UrlComponent::Path => match url_obj.path() {
+                "/" => Some(""),
+                path => Some(path),
+            },
+            UrlComponent::Query => url_obj.query(),
+            UrlComponent::Fragment => url_obj.fragment(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Strictness of a URL comparison, see [`UrlComparison::is_equivalent_at`].
+pub enum ComparisonLevel {
+    /// All components must be equal.
+    Exact,
+    /// Schemes may differ.
+    IgnoreScheme,
+    /// Hosts may differ by a leading `www.`.
+    IgnoreWww,
+    /// Queries may differ.
+    IgnoreQuery,
+    /// Fragments may differ.
+    IgnoreFragment,
+    /// Only schemes, hosts and ports must be equal.
+    SameOrigin,
+    /// Only schemes and registrable domains must be equal, e.g. `a.example.co.uk` and `b.example.co.uk`.
+    ///
+    /// Registrable domains are looked up in a bundled snapshot of the [Public Suffix List](https://publicsuffix.org),
+    /// including its private domains, so `alice.github.io` and `bob.github.io` aren't same-site. Hosts without a
+    /// registrable domain, e.g. IP addresses or public suffixes, must be equal.
+    SameSite,
+}
+
+/// Returns the registrable domain of `host`, or `host` itself if it hasn't one, see [`ComparisonLevel::SameSite`].
+fn site(host: &str) -> &str {
+    lazy_static! {
+        static ref PUBLIC_SUFFIX_LIST: List = include_str!("data/public_suffix_list.dat")
+            .parse()
+            .expect("Bundled Public Suffix List is invalid");
+    }
+
+    PUBLIC_SUFFIX_LIST
+        .domain(host.as_bytes())
+        .and_then(|domain| std::str::from_utf8(domain.as_bytes()).ok())
+        .unwrap_or(host)
+}
+
+/// Values of the components of a normalized URL, indexed by `UrlComponent as usize`.
+type Components = [Option<String>; 8];
+
+#[derive(Debug, Clone)]
+/// Result of [`urls_equivalent`].
+pub struct UrlComparison {
+    /// Components which differ between the normalized URLs.
+    pub differences: Vec<UrlComponent>,
+    a: Components,
+    b: Components,
+}
+
+/// Splits a normalized URL into its components. Relative references only have a path, a query and a fragment.
+fn components(url: &str, options: &Options) -> Result<Components, NormalizeUrlError> {
+    let (normalized, url_obj) = normalize_and_parse_url(url, options)?;
+    if let Some(url_obj) = url_obj {
+        return Ok(
+            UrlComponent::ALL.map(|component| component.value(&url_obj).map(ToOwned::to_owned))
+        );
+    }
+
+    let (rest, fragment) = match normalized.split_once('#') {
+        Some((rest, fragment)) => (rest, Some(fragment.to_owned())),
+        None => (normalized.as_str(), None),
+    };
+    let (path, query) = match rest.split_once('?') {
+        Some((path, query)) => (path, Some(query.to_owned())),
+        None => (rest, None),
+    };
+    Ok([
+        None,
+        None,
+        None,
+        None,
+        None,
+        Some(path.to_owned()),
+        query,
+        fragment,
+    ])
+}
+
+impl UrlComparison {
+    /// Returns `true` if the normalized URLs are equal.
+    pub fn is_equivalent(&self) -> bool {
+        self.differences.is_empty()
+    }
+
+    /// Returns `true` if every difference between the normalized URLs is allowed by at least one of `levels`.
+    pub fn is_equivalent_at(&self, levels: &[ComparisonLevel]) -> bool {
+        self.differences
+            .iter()
+            .all(|component| levels.iter().any(|level| self.allows(*level, *component)))
+    }
+
+    fn allows(&self, level: ComparisonLevel, component: UrlComponent) -> bool {
+        use UrlComponent::*;
+
+        match level {
+            ComparisonLevel::Exact => false,
+            ComparisonLevel::IgnoreScheme => component == Scheme,
+            ComparisonLevel::IgnoreWww => {
+                let strip_www = |components: &Components| {
+                    let host = components[Host as usize].as_deref().unwrap_or_default();
+                    host.strip_prefix("www.").unwrap_or(host).to_owned()
+                };
+                component == Host && strip_www(&self.a) == strip_www(&self.b)
+            }
+            ComparisonLevel::IgnoreQuery => component == Query,
+            ComparisonLevel::IgnoreFragment => component == Fragment,
+            ComparisonLevel::SameOrigin => !matches!(component, Scheme | Host | Port),
+            ComparisonLevel::SameSite => match component {
+                Scheme => false,
+                Host => {
+                    let host = |components: &Components| components[Host as usize].clone();
+                    match (host(&self.a), host(&self.b)) {
+                        (Some(a), Some(b)) => site(&a) == site(&b),
+                        _ => false,
+                    }
+                }
+                _ => true,
+            },
+        }
+    }
+}
+
+/// Normalizes two URLs with [`normalize_url`](crate::normalize_url) and reports which components differ.
+///
+/// Relative references only have a path, a query and a fragment, so they always differ from absolute URLs.
+///
+/// ```
+/// use normalize_url_rs::{urls_equivalent, ComparisonLevel, OptionsBuilder, UrlComponent};
+///
+/// let options = OptionsBuilder::default().build().unwrap();
+/// let comparison = urls_equivalent("http://example.com/a?x=1", "https://www.example.com/a/", &options).unwrap();
+///
+/// assert_eq!(comparison.differences, vec![UrlComponent::Scheme, UrlComponent::Query]);
+/// assert!(!comparison.is_equivalent());
+/// assert!(comparison.is_equivalent_at(&[ComparisonLevel::IgnoreScheme, ComparisonLevel::IgnoreQuery]));
+/// ```
+pub fn urls_equivalent(
+    a: &str,
+    b: &str,
+    options: &Options,
+) -> Result<UrlComparison, NormalizeUrlError> {
+    let a = components(a, options)?;
+    let b = components(b, options)?;
+
+    let differences = UrlComponent::ALL
+        .into_iter()
+        .enumerate()
+        .filter(|(index, _)| a[*index] != b[*index])
+        .map(|(_, component)| component)
+        .collect();
+
+    Ok(UrlComparison { differences, a, b })
+}
@@ -0,0 +1,105 @@
+use fancy_regex::{Captures, Regex};
+use lazy_static::lazy_static;
+
+/// Reverses common defanging conventions of threat-intelligence indicators.
+///
+/// - Defanged schemes: `hxxp`, `hXXp`, `hxtp`, `htxp`, `h**p`, `h[tt]p` and `meow` → `http`, `fxp` and `fxxp` → `ftp`,
+///   each with an optional `s`
+/// - Bracketed separators (in `[]`, `()` or `{}`, optionally surrounded by spaces): `.` and `dot` → `.`, `:` → `:`,
+///   `/` → `/`, `//` → `//`, `://` → `://`, `@` and `at` → `@`
+/// - Escaped dots: `\.` → `.`
+///
+/// Separators are only replaced in the scheme and the authority, so paths like `/wiki/Meet_(at)_noon` are kept intact.
+///
+/// ```
+/// use normalize_url_rs::refang;
+///
+/// assert_eq!(refang("hxxps[:]//evil[.]com/path"), "https://evil.com/path");
+/// assert_eq!(refang("evil(dot)com"), "evil.com");
+/// ```
+pub fn refang(input: &str) -> String {
+    lazy_static! {
+        static ref SEPARATOR_RE: Regex = Regex::new(
+            r"(?i)^(?:\s*[\[\(\{]\s*(\.|dot|:\/\/|:|\/\/|\/|@|at)\s*[\]\)\}]\s*|\\(\.))"
+        )
+        .unwrap();
+        static ref HTTP_RE: Regex =
+            Regex::new(r"(?i)^(?:hxxp|hxtp|htxp|h\*\*p|h\[tt\]p|meow)(s?):").unwrap();
+        static ref FTP_RE: Regex = Regex::new(r"(?i)^(?:fxp|fxxp)(s?):").unwrap();
+    }
+
+    let input = input.trim();
+    let mut url = String::new();
+    let mut index = 0;
+    while index < input.len() {
+        let rest = &input[index..];
+        // `/` after `:` is part of `://`, otherwise it starts the path
+        let is_scheme_slash = url.ends_with(':') || url.ends_with(":/");
+
+        if let Ok(Some(captures)) = SEPARATOR_RE.captures(rest) {
+            let separator = captures.get(1).or(captures.get(2)).unwrap().as_str();
+            let separator = match separator.to_lowercase().as_str() {
+                "dot" => ".".to_owned(),
+                "at" => "@".to_owned(),
+                _ => separator.to_owned(),
+            };
+            index += captures[0].len();
+            url += &separator;
+            if separator == "/" && !is_scheme_slash {
+                break;
+            }
+            continue;
+        }
+
+        let c = rest.chars().next().unwrap();
+        if c == '?' || c == '#' || (c == '/' && !is_scheme_slash) {
+            break;
+        }
+        url.push(c);
+        index += c.len_utf8();
+    }
+    url += &input[index..];
+
+    let url = HTTP_RE.replace(&url, |captures: &Captures| {
+        format!("http{}:", captures[1].to_lowercase())
+    });
+    let url = FTP_RE.replace(&url, |captures: &Captures| {
+        format!("ftp{}:", captures[1].to_lowercase())
+    });
+
+    url.into_owned()
+}
+
+/// Defangs a URL for safe display: `http`, `https`, `ftp` and `ftps` schemes become `hxxp`, `hxxps`, `fxp` and `fxps`,
+/// and dots of the host become `[.]`. [`refang`] reverses it.
+///
+/// ```
+/// use normalize_url_rs::defang;
+///
+/// assert_eq!(defang("https://evil.com/a.php"), "hxxps://evil[.]com/a.php");
+/// ```
+pub fn defang(url: &str) -> String {
+    lazy_static! {
+        static ref SCHEME_RE: Regex = Regex::new(r"(?i)^([a-z][a-z\d+.-]*):(\/\/)?").unwrap();
+    }
+
+    let (scheme, rest) = match SCHEME_RE.captures(url).ok().flatten() {
+        Some(captures) => {
+            let scheme = match captures[1].to_lowercase().as_str() {
+                "http" => "hxxp".to_owned(),
+                "https" => "hxxps".to_owned(),
+                "ftp" => "fxp".to_owned(),
+                "ftps" => "fxps".to_owned(),
+                _ => captures[1].to_owned(),
+            };
+            (
+                format!("{}:{}", scheme, captures.get(2).map_or("", |m| m.as_str())),
+                &url[captures[0].len()..],
+            )
+        }
+        None => (String::new(), url),
+    };
+
+    let (authority, path) = rest.split_at(rest.find(['/', '?', '#']).unwrap_or(rest.len()));
+    format!("{}{}{}", scheme, authority.replace('.', "[.]"), path)
+}
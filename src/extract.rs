@@ -0,0 +1,264 @@
+use fancy_regex::Regex;
+use lazy_static::lazy_static;
+
+use crate::{normalize_url, NormalizeUrlError, Options};
+
+/// Invisible characters removed anywhere in the input.
+const ZERO_WIDTH_CHARACTERS: [char; 3] = ['\u{200B}', '\u{2060}', '\u{FEFF}'];
+
+/// Pairs of characters enclosing a URL.
+const ENCLOSING_PAIRS: [(char, char); 9] = [
+    ('"', '"'),
+    ('\'', '\''),
+    ('`', '`'),
+    ('<', '>'),
+    ('(', ')'),
+    ('[', ']'),
+    ('“', '”'),
+    ('‘', '’'),
+    ('«', '»'),
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// Wrapping removed by [`extract_url`], holding the exact removed text.
+pub enum Removed {
+    /// Leading or trailing whitespace, including non-breaking spaces, or zero-width characters anywhere.
+    Whitespace(String),
+    /// A leading label, e.g. `URL: `.
+    Label(String),
+    /// Markdown link syntax before and after the URL, e.g. `[text](` and ` "title")`.
+    MarkdownLink(String, String),
+    /// A pair of quotes or brackets enclosing the URL, e.g. `<` and `>`.
+    Enclosing(String, String),
+    /// Unmatched opening quotes or brackets before the URL.
+    LeadingPunctuation(String),
+    /// Punctuation after the URL, e.g. `).` in `(example.com/page).`. Closing brackets are only
+    /// removed when they're unbalanced, so `https://en.wikipedia.org/wiki/Rust_(programming_language)` is kept intact.
+    TrailingPunctuation(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// Result of [`extract_url`] and [`normalize_url_lenient`].
+pub struct ExtractedUrl {
+    /// The extracted URL, normalized by [`normalize_url_lenient`].
+    pub url: String,
+    /// The removed wrapping, in the order it was removed.
+    pub removed: Vec<Removed>,
+}
+
+/// Brackets which may be part of a URL, e.g. `https://en.wikipedia.org/wiki/Rust_(programming_language)`.
+const BRACKETS: [(char, char); 3] = [('(', ')'), ('[', ']'), ('{', '}')];
+
+fn is_space(c: char) -> bool {
+    c.is_whitespace() || matches!(c, '\u{200C}' | '\u{200D}')
+}
+
+/// Returns `true` if the `opening` character at the start of `url` is closed by the `closing` character at its end,
+/// e.g. not for `(a)_(b)`.
+fn is_enclosed(url: &str, opening: char, closing: char) -> bool {
+    if opening == closing {
+        return true;
+    }
+
+    let mut depth = 0;
+    for (index, c) in url.char_indices() {
+        if c == opening {
+            depth += 1;
+        } else if c == closing {
+            depth -= 1;
+            if depth == 0 {
+                return index + c.len_utf8() == url.len();
+            }
+        }
+    }
+    false
+}
+
+fn remove_spaces(url: &mut &str, removed: &mut Vec<Removed>) {
+    let start = url.len() - url.trim_start_matches(is_space).len();
+    if start > 0 {
+        removed.push(Removed::Whitespace(url[..start].to_owned()));
+        *url = &url[start..];
+    }
+    let end = url.trim_end_matches(is_space).len();
+    if end < url.len() {
+        removed.push(Removed::Whitespace(url[end..].to_owned()));
+        *url = &url[..end];
+    }
+}
+
+fn remove_label(url: &mut &str, removed: &mut Vec<Removed>) {
+    lazy_static! {
+        static ref RE: Regex = Regex::new(
+            r"(?i)^(?:url|uri|link|source|href|website|homepage)\s*[:=]\s*(?!\/\/)(?=\S)"
+        )
+        .unwrap();
+    }
+    if let Ok(Some(label)) = RE.find(url) {
+        removed.push(Removed::Label(label.as_str().to_owned()));
+        *url = &url[label.end()..];
+    }
+}
+
+fn remove_markdown_link(url: &mut &str, removed: &mut Vec<Removed>) {
+    lazy_static! {
+        static ref RE: Regex =
+            Regex::new(r"^(\[[^\]]*\]\(\s*)(.*?)(\s+(?:\x22[^\x22]*\x22|'[^']*')\s*\)|\s*\))$")
+                .unwrap();
+    }
+    if let Ok(Some(captures)) = RE.captures(url) {
+        removed.push(Removed::MarkdownLink(
+            captures[1].to_owned(),
+            captures[3].to_owned(),
+        ));
+        *url = captures.get(2).unwrap().as_str();
+    }
+}
+
+fn remove_enclosing(url: &mut &str, removed: &mut Vec<Removed>) {
+    for (opening, closing) in ENCLOSING_PAIRS {
+        if url.len() > opening.len_utf8() + closing.len_utf8()
+            && url.starts_with(opening)
+            && url.ends_with(closing)
+            && is_enclosed(url, opening, closing)
+        {
+            *url = &url[opening.len_utf8()..url.len() - closing.len_utf8()];
+            removed.push(Removed::Enclosing(opening.to_string(), closing.to_string()));
+            return;
+        }
+    }
+}
+
+fn remove_leading_punctuation(url: &mut &str, removed: &mut Vec<Removed>) {
+    let rest = url.trim_start_matches(|c| ENCLOSING_PAIRS.iter().any(|(opening, _)| *opening == c));
+    let start = url.len() - rest.len();
+    if start > 0 {
+        removed.push(Removed::LeadingPunctuation(url[..start].to_owned()));
+        *url = rest;
+    }
+}
+
+fn remove_trailing_punctuation(url: &mut &str, removed: &mut Vec<Removed>) {
+    // Depths of the brackets before each character, in a single pass. Openings are only matched by the closings
+    // following them, e.g. `)` isn't balanced in `a)_(b)`
+    let mut depths = [0usize; 3];
+    let characters = url
+        .char_indices()
+        .map(|(index, c)| {
+            let depths_before = depths;
+            for (depth, (opening, closing)) in depths.iter_mut().zip(BRACKETS) {
+                if c == opening {
+                    *depth += 1;
+                } else if c == closing {
+                    *depth = depth.saturating_sub(1);
+                }
+            }
+            (index, c, depths_before)
+        })
+        .collect::<Vec<_>>();
+
+    let mut end = url.len();
+    for (index, c, depths) in characters.into_iter().rev() {
+        let is_removed = match c {
+            '.' | ',' | ';' | ':' | '!' | '?' | '"' | '\'' | '`' | '>' | '”' | '’' | '»' => {
+                true
+            }
+            // Closing brackets are only removed when they're unbalanced
+            ')' => depths[0] == 0,
+            ']' => depths[1] == 0,
+            '}' => depths[2] == 0,
+            _ => false,
+        };
+        if !is_removed {
+            break;
+        }
+        end = index;
+    }
+    if end < url.len() {
+        removed.push(Removed::TrailingPunctuation(url[end..].to_owned()));
+        *url = &url[..end];
+    }
+}
+
+/// Extracts a URL from messy input, e.g. `URL: <https://example.com/page>.` → `https://example.com/page`.
+///
+/// The following rules are applied until none of them changes the input anymore:
+/// 1. Zero-width spaces, word joiners and byte order marks are removed anywhere, then leading and trailing
+///    whitespace, including non-breaking spaces, is removed.
+/// 2. A leading label (`URL:`, `URI:`, `Link:`, `Source:`, `Href:`, `Website:` or `Homepage:`) is removed.
+/// 3. Markdown link syntax `[text](url "title")` is reduced to the URL.
+/// 4. A pair of quotes or brackets enclosing the whole URL is removed, but not the brackets of `(a)_(b)`.
+/// 5. Opening quotes and brackets before the URL are removed.
+/// 6. Punctuation after the URL is removed, except closing brackets balanced by an opening one in the URL.
+///
+/// ```
+/// use normalize_url_rs::{extract_url, Removed};
+///
+/// let extracted = extract_url("(https://en.wikipedia.org/wiki/Rust_(programming_language)).");
+/// assert_eq!(extracted.url, "https://en.wikipedia.org/wiki/Rust_(programming_language)");
+/// assert_eq!(extracted.removed, vec![
+///     Removed::LeadingPunctuation("(".to_string()),
+///     Removed::TrailingPunctuation(").".to_string()),
+/// ]);
+/// ```
+pub fn extract_url(input: &str) -> ExtractedUrl {
+    let mut input = input.to_owned();
+    let mut removed = vec![];
+
+    if input.contains(ZERO_WIDTH_CHARACTERS) {
+        removed.push(Removed::Whitespace(
+            input
+                .chars()
+                .filter(|c| ZERO_WIDTH_CHARACTERS.contains(c))
+                .collect(),
+        ));
+        input.retain(|c| !ZERO_WIDTH_CHARACTERS.contains(&c));
+    }
+
+    // Rules shrink a slice of the input, so removing wrapping never moves the rest of it
+    let mut url = input.as_str();
+
+    loop {
+        let length = removed.len();
+        remove_spaces(&mut url, &mut removed);
+        remove_label(&mut url, &mut removed);
+        remove_markdown_link(&mut url, &mut removed);
+        remove_enclosing(&mut url, &mut removed);
+        remove_leading_punctuation(&mut url, &mut removed);
+        remove_trailing_punctuation(&mut url, &mut removed);
+        if removed.len() == length {
+            break;
+        }
+    }
+
+    ExtractedUrl {
+        url: url.to_owned(),
+        removed,
+    }
+}
+
+/// Extracts a URL from messy input with [`extract_url`], then normalizes it with [`normalize_url`].
+///
+/// ```
+/// use normalize_url_rs::{normalize_url_lenient, OptionsBuilder, Removed};
+///
+/// let options = OptionsBuilder::default().build().unwrap();
+/// let result = normalize_url_lenient("\u{a0}\"www.example.com/page\",", &options).unwrap();
+///
+/// assert_eq!(result.url, "http://example.com/page");
+/// assert_eq!(result.removed, vec![
+///     Removed::Whitespace("\u{a0}".to_string()),
+///     Removed::LeadingPunctuation("\"".to_string()),
+///     Removed::TrailingPunctuation("\",".to_string()),
+/// ]);
+/// ```
+pub fn normalize_url_lenient(
+    input: &str,
+    options: &Options,
+) -> Result<ExtractedUrl, NormalizeUrlError> {
+    let extracted = extract_url(input);
+    Ok(ExtractedUrl {
+        url: normalize_url(&extracted.url, options)?,
+        removed: extracted.removed,
+    })
+}
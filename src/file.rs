@@ -0,0 +1,186 @@
+use fancy_regex::Regex;
+use lazy_static::lazy_static;
+use percent_encoding::{utf8_percent_encode, AsciiSet, CONTROLS};
+use urlencoding::decode;
+
+use crate::{NormalizeUrlError, Options};
+
+/// Characters encoded in path segments: the URL Standard path percent-encode set, plus `\`.
+const PATH_SEGMENT_ENCODE_SET: &AsciiSet = &CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'#')
+    .add(b'<')
+    .add(b'>')
+    .add(b'?')
+    .add(b'`')
+    .add(b'{')
+    .add(b'}')
+    .add(b'\\');
+
+/// Characters encoded in decoded path segments, which can't contain percent-encoded octets anymore.
+const DECODED_PATH_SEGMENT_ENCODE_SET: &AsciiSet = &PATH_SEGMENT_ENCODE_SET.add(b'%');
+
+fn is_drive_letter(segment: &str) -> bool {
+    lazy_static! {
+        static ref RE: Regex = Regex::new(r"^[A-Za-z][:|]$").unwrap();
+    }
+    RE.is_match(segment).unwrap_or(false)
+}
+
+/// Returns `.` or `..` for dot segments, including percent-encoded ones such as `%2e%2E` in URLs, or `segment` otherwise.
+pub(crate) fn dot_segment(segment: &str, is_url: bool) -> &str {
+    if !is_url {
+        return segment;
+    }
+
+    match segment.to_ascii_lowercase().as_str() {
+        "%2e" => ".",
+        ".%2e" | "%2e." | "%2e%2e" => "..",
+        _ => segment,
+    }
+}
+
+/// Percent-encodes a path segment. Segments of `file:` URLs are decoded first, unless they're not
+/// valid UTF-8 or decoding would introduce a path separator.
+pub(crate) fn encode_segment(segment: &str, is_url: bool) -> String {
+    if !is_url {
+        return utf8_percent_encode(segment, DECODED_PATH_SEGMENT_ENCODE_SET).to_string();
+    }
+
+    match decode(segment) {
+        Ok(decoded) if !decoded.contains(['/', '\\']) => {
+            utf8_percent_encode(&decoded, DECODED_PATH_SEGMENT_ENCODE_SET).to_string()
+        }
+        _ => utf8_percent_encode(segment, PATH_SEGMENT_ENCODE_SET).to_string(),
+    }
+}
+
+/// Splits the part of a `file:` URL following the scheme, or a local path, into its host and path.
+fn split_host(input: &str) -> (Option<String>, String) {
+    let rest = input.replace('\\', "/");
+
+    let Some(authority_and_path) = rest.strip_prefix("//") else {
+        return (None, rest);
+    };
+
+    let (authority, path) = match authority_and_path.find('/') {
+        Some(index) => authority_and_path.split_at(index),
+        None => (authority_and_path, ""),
+    };
+
+    // `file://C:/Docs` and `//C:/Docs` are drive paths, not UNC hosts
+    if is_drive_letter(authority) {
+        return (None, format!("/{}", authority_and_path));
+    }
+
+    let host = authority.to_lowercase();
+    match host.as_str() {
+        "" | "localhost" => (None, path.to_owned()),
+        _ => (Some(host), path.to_owned()),
+    }
+}
+
+/// Normalizes a `file:` URL, or converts a local Windows or POSIX path into a normalized `file:` URL.
+///
+/// The conversion is pure string handling, so it doesn't depend on the host OS:
+/// - `C:\Docs\a.txt`, `file:///c|/Docs/a.txt` and `file://C:/Docs/a.txt` → `file:///C:/Docs/a.txt`
+/// - `\\server\share\x` and `file://Server/share/x` → `file://server/share/x`
+/// - `/home/u/a.txt` → `file:///home/u/a.txt`
+///
+/// `.` and `..` segments, including percent-encoded ones in `file:` URLs, are resolved, duplicate slashes are removed, and path segments of `file:` URLs are percent-decoded
+/// as long as that doesn't introduce a path separator. Local paths are used verbatim, so `%` in them is encoded.
+/// Relative paths are rejected with [`NormalizeUrlError::RelativeFilePath`].
+///
+/// ```
+/// use normalize_url_rs::{normalize_file_url, OptionsBuilder};
+///
+/// let options = OptionsBuilder::default().build().unwrap();
+/// let result = normalize_file_url(r"c:\Docs\..\My Docs\a.txt", &options);
+///
+/// assert_eq!(result.unwrap(), "file:///C:/My%20Docs/a.txt")
+/// ```
+pub fn normalize_file_url(input: &str, options: &Options) -> Result<String, NormalizeUrlError> {
+    lazy_static! {
+        static ref RE: Regex = Regex::new(r"(?i)^file:").unwrap();
+    }
+
+    let input = input.trim();
+    let is_url = RE
+        .is_match(input)
+        .map_err(Into::into)
+        .map_err(NormalizeUrlError::UnexpectedError)?;
+
+    // Local paths have no query nor fragment
+    let (input, query, fragment) = match is_url {
+        true => {
+            let (input, fragment) = match input.split_once('#') {
+                Some((input, fragment)) => (input, Some(fragment)),
+                None => (input, None),
+            };
+            let (input, query) = match input.split_once('?') {
+                Some((input, query)) => (input, Some(query)),
+                None => (input, None),
+            };
+            (&input[5..], query, fragment)
+        }
+        false => (input, None, None),
+    };
+
+    let (host, path) = split_host(input);
+
+    let mut segments = path.split('/').peekable();
+    let mut drive = None;
+    let mut resolved: Vec<String> = vec![];
+    let mut is_directory = false;
+
+    // Either an absolute path, or one starting with a drive letter
+    match segments.peek() {
+        Some(&"") => {
+            segments.next();
+        }
+        Some(segment) if is_drive_letter(segment) => {}
+        _ => return Err(NormalizeUrlError::RelativeFilePath),
+    }
+
+    if host.is_none() {
+        if let Some(segment) = segments.next_if(|segment| is_drive_letter(segment)) {
+            drive = Some(format!("{}:", segment[..1].to_uppercase()));
+            is_directory = true;
+        }
+    }
+
+    for segment in segments {
+        let segment = dot_segment(segment, is_url);
+        is_directory = matches!(segment, "" | "." | "..");
+        match segment {
+            "" | "." => {}
+            ".." => {
+                resolved.pop();
+            }
+            segment => resolved.push(encode_segment(segment, is_url)),
+        }
+    }
+
+    let is_root = resolved.is_empty();
+    if let Some(drive) = drive {
+        resolved.insert(0, drive);
+    }
+    let mut path = format!("/{}", resolved.join("/"));
+    if is_directory && !path.ends_with('/') && (!options.remove_trailing_slash || is_root) {
+        path.push('/');
+    }
+
+    let mut result = format!("file://{}{}", host.unwrap_or_default(), path);
+    if let Some(query) = query.filter(|query| !query.is_empty()) {
+        result += "?";
+        result += query;
+    }
+    if let Some(fragment) = fragment.filter(|fragment| !options.strip_hash && !fragment.is_empty())
+    {
+        result += "#";
+        result += fragment;
+    }
+
+    Ok(result)
+}
@@ -0,0 +1,106 @@
+use crate::{normalize_and_parse_url, normalize_url, NormalizeUrlError, Options};
+
+const FNV_OFFSET_BASIS_64: u64 = 0xcbf29ce484222325;
+const FNV_PRIME_64: u64 = 0x100000001b3;
+const FNV_OFFSET_BASIS_128: u128 = 0x6c62272e07bb014262b821756295c58d;
+const FNV_PRIME_128: u128 = 0x1000000000000000000013b;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// Version of the fingerprint algorithm. A version never changes once released.
+pub enum FingerprintVersion {
+    /// 64-bit and 128-bit FNV-1a of the UTF-8 bytes.
+    V1,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// Fingerprint of a normalized URL or one of its components, see [`fingerprint`].
+pub struct Fingerprint {
+    /// Version of the algorithm which computed the hashes.
+    pub version: FingerprintVersion,
+    /// 64-bit hash.
+    pub hash64: u64,
+    /// 128-bit hash.
+    pub hash128: u128,
+}
+
+impl Fingerprint {
+    /// Computes the fingerprint of `input` with the `V1` algorithm.
+    pub fn of(input: &str) -> Self {
+        let mut hash64 = FNV_OFFSET_BASIS_64;
+        let mut hash128 = FNV_OFFSET_BASIS_128;
+        for byte in input.bytes() {
+            hash64 = (hash64 ^ byte as u64).wrapping_mul(FNV_PRIME_64);
+            hash128 = (hash128 ^ byte as u128).wrapping_mul(FNV_PRIME_128);
+        }
+
+        Fingerprint {
+            version: FingerprintVersion::V1,
+            hash64,
+            hash128,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// Fingerprints of a normalized URL and of its components, see [`component_fingerprints`].
+pub struct ComponentFingerprints {
+    /// Fingerprint of the whole normalized URL, same as [`fingerprint`].
+    pub url: Fingerprint,
+    /// Fingerprint of the host, if any.
+    pub host: Option<Fingerprint>,
+    /// Fingerprint of the path. The empty path of hierarchical URLs is `/`.
+    pub path: Fingerprint,
+    /// Fingerprint of the query, without `?`, if any.
+    pub query: Option<Fingerprint>,
+}
+
+/// Computes the fingerprint of the URL normalized with [`normalize_url`], i.e. of its final canonical form.
+///
+/// The hashes are 64-bit and 128-bit FNV-1a of the UTF-8 bytes of the normalized URL, so they don't depend on the
+/// platform. They only depend on the normalized URL, which is frozen for a given [`NormalizationVersion`](crate::NormalizationVersion): stored
+/// fingerprints stay valid across releases of this crate as long as URLs are normalized with the same options, including
+/// [`Options::version`]. URLs only get the same fingerprint when they're normalized with the same options.
+///
+/// ```
+/// use normalize_url_rs::{fingerprint, OptionsBuilder};
+///
+/// let options = OptionsBuilder::default().build().unwrap();
+/// let result = fingerprint("HTTPS://www.example.com/", &options).unwrap();
+///
+/// assert_eq!(result.hash64, 0x837b2b5793a240b3)
+/// ```
+pub fn fingerprint(url: &str, options: &Options) -> Result<Fingerprint, NormalizeUrlError> {
+    Ok(Fingerprint::of(&normalize_url(url, options)?))
+}
+
+/// Computes the fingerprints of the URL normalized with [`normalize_url`] and of its host, path and query, e.g. for bucketing.
+pub fn component_fingerprints(
+    url: &str,
+    options: &Options,
+) -> Result<ComponentFingerprints, NormalizeUrlError> {
+    let normalized = normalize_url(url, options)?;
+    let (normalized_with_protocol, url_obj) = normalize_and_parse_url(url, options)?;
+    if let Some(url_obj) = url_obj {
+        return Ok(ComponentFingerprints {
+            url: Fingerprint::of(&normalized),
+            host: url_obj.host_str().map(Fingerprint::of),
+            path: Fingerprint::of(url_obj.path()),
+            query: url_obj.query().map(Fingerprint::of),
+        });
+    }
+
+    // Relative references have no host
+    let rest = normalized_with_protocol
+        .split_once('#')
+        .map_or(normalized_with_protocol.as_str(), |(rest, _)| rest);
+    let (path, query) = match rest.split_once('?') {
+        Some((path, query)) => (path, Some(query)),
+        None => (rest, None),
+    };
+    Ok(ComponentFingerprints {
+        url: Fingerprint::of(&normalized),
+        host: None,
+        path: Fingerprint::of(path),
+        query: query.map(Fingerprint::of),
+    })
+}
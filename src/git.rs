@@ -0,0 +1,122 @@
+use std::collections::HashMap;
+
+use derive_builder::Builder;
+use fancy_regex::Regex;
+use lazy_static::lazy_static;
+
+use crate::{default_port_table, parse_url_string, NormalizeUrlError};
+
+#[derive(Builder, Debug, Clone)]
+#[builder(setter(into))]
+/// Git remote normalization options, see [`normalize_git_remote`].
+pub struct GitRemoteOptions {
+    /// Scheme of the canonical form.
+    ///
+    /// Default value: `https`.
+    #[builder(default = "\"https\".to_string()")]
+    pub scheme: String,
+    /// Hosts on which owner and repository names are case-insensitive, and will be lowercased.
+    ///
+    /// Default value: `github.com`, `gitlab.com`, `bitbucket.org`.
+    #[builder(
+        default = "vec![\"github.com\".to_string(), \"gitlab.com\".to_string(), \"bitbucket.org\".to_string()]"
+    )]
+    pub case_insensitive_hosts: Vec<String>,
+    /// Default ports of the transports, removed from the canonical form.
+    ///
+    /// Default value: same as `Options::default_ports`, including `ssh` (22) and `git` (9418).
+    #[builder(default = "default_port_table()")]
+    pub default_ports: HashMap<String, u16>,
+}
+
+/// Normalizes a git remote URL to a canonical form: `<scheme>://<host>/<path>`.
+///
+/// Accepts the scp-like syntax (`git@github.com:owner/repo.git`), `ssh://`, `git://`, `http(s)://`
+/// and `git+<transport>://` URLs. Username, password, default ports and the `.git` suffix are removed,
+/// and the host is lowercased.
+///
+/// Local paths (`/srv/repo.git`, `./repo`, `~/repo`, `C:\repo`) and `file:` URLs are rejected with
+/// [`NormalizeUrlError::LocalGitRemote`], since they don't identify a repository across machines.
+///
+/// Non-default ports are kept, e.g. `ssh://git@git.example.com:2222/repo` → `https://git.example.com:2222/repo`,
+/// since they may be served by a different server than the default port. The result identifies the repository,
+/// it isn't necessarily reachable with `<scheme>`.
+///
+/// ```
+/// use normalize_url_rs::{normalize_git_remote, GitRemoteOptionsBuilder};
+///
+/// let options = GitRemoteOptionsBuilder::default().build().unwrap();
+/// let result = normalize_git_remote("git@github.com:Owner/Repo.git", &options);
+///
+/// assert_eq!(result.unwrap(), "https://github.com/owner/repo")
+/// ```
+pub fn normalize_git_remote(
+    url: &str,
+    options: &GitRemoteOptions,
+) -> Result<String, NormalizeUrlError> {
+    lazy_static! {
+        static ref LOCAL_RE: Regex =
+            Regex::new(r"(?i)^(?:[\/\\.~]|[a-z]:(?:[\/\\]|$)|file:)").unwrap();
+        static ref SCP_RE: Regex = Regex::new(r"^(?:[^@/]+@)?([^@/:]+):(?!\/\/)(.*)$").unwrap();
+        static ref GIT_PLUS_RE: Regex = Regex::new(r"(?i)^git\+").unwrap();
+        static ref SCHEME_RE: Regex = Regex::new(r"^[a-zA-Z][a-zA-Z\d+\-.]*:\/\/").unwrap();
+        static ref SLASHES_RE: Regex = Regex::new(r"\/{2,}").unwrap();
+        static ref SUFFIX_RE: Regex = Regex::new(r"(?i)(?:\.git)?\/*$").unwrap();
+    }
+
+    let url_string = GIT_PLUS_RE.replace(url.trim(), "").to_string();
+
+    if LOCAL_RE
+        .is_match(&url_string)
+        .map_err(Into::into)
+        .map_err(NormalizeUrlError::UnexpectedError)?
+    {
+        return Err(NormalizeUrlError::LocalGitRemote);
+    }
+
+    let url_string = if SCHEME_RE
+        .is_match(&url_string)
+        .map_err(Into::into)
+        .map_err(NormalizeUrlError::UnexpectedError)?
+    {
+        url_string
+    } else if let Some(captures) = SCP_RE
+        .captures(&url_string)
+        .map_err(Into::into)
+        .map_err(NormalizeUrlError::UnexpectedError)?
+    {
+        format!(
+            "ssh://{}/{}",
+            &captures[1],
+            captures[2].trim_start_matches('/')
+        )
+    } else {
+        format!("{}://{}", options.scheme, url_string)
+    };
+
+    let url_obj = parse_url_string(&url_string)?;
+    let host = url_obj
+        .host_str()
+        .ok_or(NormalizeUrlError::URLError)?
+        .trim_end_matches('.')
+        .to_lowercase();
+    let port = match url_obj.port() {
+        Some(port) if options.default_ports.get(url_obj.scheme()) != Some(&port) => {
+            format!(":{}", port)
+        }
+        _ => String::new(),
+    };
+
+    let path = SLASHES_RE.replace_all(url_obj.path(), "/");
+    let path = SUFFIX_RE.replace(&path, "");
+    let path = match options
+        .case_insensitive_hosts
+        .iter()
+        .any(|case_insensitive_host| case_insensitive_host.eq_ignore_ascii_case(&host))
+    {
+        true => path.to_lowercase(),
+        false => path.to_string(),
+    };
+
+    Ok(format!("{}://{}{}{}", options.scheme, host, port, path))
+}
@@ -0,0 +1,102 @@
+use unicode_script::{Script, UnicodeScript};
+use unicode_security::{skeleton, MixedScript};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// Confusable-domain analysis of a single host label.
+pub struct LabelAnalysis {
+    /// The label as it appears in the normalized host, e.g. `xn--pple-43d`.
+    pub ascii: String,
+    /// The label decoded from Punycode, e.g. `аpple`.
+    pub unicode: String,
+    /// Full names of the scripts used by the label, in order of first appearance.
+    ///
+    /// Characters shared between scripts (digits, `-`) are not reported.
+    pub scripts: Vec<&'static str>,
+    /// The label mixes characters from scripts that are not normally used together.
+    pub mixed_script: bool,
+    /// The label is written in a single non-ASCII script, but its skeleton is plain ASCII,
+    /// i.e. it can pass for an ASCII label.
+    pub whole_script_confusable: bool,
+    /// The [UTS #39 skeleton](https://www.unicode.org/reports/tr39/#Confusable_Detection) of the label.
+    pub skeleton: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// Confusable-domain analysis of a host, see [`analyze_host`].
+pub struct HostAnalysis {
+    /// Analysis of every label of the host, in order.
+    pub labels: Vec<LabelAnalysis>,
+}
+
+impl HostAnalysis {
+    /// Returns `true` if any label mixes scripts or is whole-script confusable.
+    pub fn is_suspicious(&self) -> bool {
+        self.labels
+            .iter()
+            .any(|label| label.mixed_script || label.whole_script_confusable)
+    }
+
+    /// Skeleton of the whole host, suitable for comparing against the skeletons of protected hosts.
+    pub fn skeleton(&self) -> String {
+        self.labels
+            .iter()
+            .map(|label| label.skeleton.as_str())
+            .collect::<Vec<_>>()
+            .join(".")
+    }
+}
+
+fn analyze_label(ascii: &str) -> LabelAnalysis {
+    let unicode = match ascii.get(..4) {
+        Some(prefix) if prefix.eq_ignore_ascii_case("xn--") => {
+            idna::punycode::decode_to_string(&ascii[4..]).unwrap_or_else(|| ascii.to_owned())
+        }
+        _ => ascii.to_owned(),
+    };
+
+    let mut scripts = vec![];
+    for c in unicode.chars() {
+        let script = c.script();
+        if matches!(script, Script::Common | Script::Inherited | Script::Unknown) {
+            continue;
+        }
+        if !scripts.contains(&script.full_name()) {
+            scripts.push(script.full_name());
+        }
+    }
+
+    let mixed_script = !unicode.as_str().is_single_script();
+    let skeleton = skeleton(&unicode).collect::<String>();
+    let whole_script_confusable = !mixed_script && !unicode.is_ascii() && skeleton.is_ascii();
+
+    LabelAnalysis {
+        ascii: ascii.to_owned(),
+        unicode,
+        scripts,
+        mixed_script,
+        whole_script_confusable,
+        skeleton,
+    }
+}
+
+/// Analyses a host for homograph and confusable-domain attacks.
+///
+/// Expects a host as returned by [`crate::normalize_url`], i.e. with non-ASCII labels already converted to Punycode.
+///
+/// ```
+/// use normalize_url_rs::analyze_host;
+///
+/// let analysis = analyze_host("xn--pple-43d.com");
+/// assert!(analysis.labels[0].mixed_script);
+/// assert_eq!(analysis.labels[0].scripts, vec!["Cyrillic", "Latin"]);
+/// assert_eq!(analysis.skeleton(), analyze_host("apple.com").skeleton());
+/// ```
+pub fn analyze_host(host: &str) -> HostAnalysis {
+    HostAnalysis {
+        labels: host
+            .trim_end_matches('.')
+            .split('.')
+            .map(analyze_label)
+            .collect(),
+    }
+}
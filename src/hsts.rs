@@ -0,0 +1,159 @@
+use fancy_regex::Regex;
+use lazy_static::lazy_static;
+use std::collections::HashMap;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use crate::NormalizeUrlError;
+
+#[derive(Debug, Clone, Default)]
+/// A list of hosts that are only served over HTTPS, e.g. taken from the
+/// [Chromium HSTS preload list](https://hstspreload.org).
+///
+/// Each line contains a host, optionally followed by `include_subdomains`.
+/// Blank lines and lines starting with `#` are ignored:
+///
+/// ```text
+/// # Intranet hosts
+/// intranet.example.com
+/// example.org include_subdomains
+/// ```
+pub struct HstsPreloadList {
+    hosts: Arc<HashMap<String, bool>>,
+}
+
+impl HstsPreloadList {
+    /// Returns the bundled sample of the Chromium HSTS preload list.
+    ///
+    /// It only contains a few dozen hand-picked entries (preloaded TLDs such as `dev` and `app`, and popular hosts),
+    /// it isn't a snapshot of the full list and isn't kept in sync with it. Load the full list with
+    /// [`HstsPreloadList::from_chromium_json`] to upgrade every preloaded host.
+    pub fn sample() -> Self {
+        lazy_static! {
+            static ref SAMPLE: HstsPreloadList = include_str!("data/hsts_preload_sample.txt")
+                .parse()
+                .expect("Bundled HSTS preload list sample is invalid");
+        }
+        SAMPLE.clone()
+    }
+
+    /// Reads a host list from a file.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, NormalizeUrlError> {
+        std::fs::read_to_string(path)
+            .map_err(Into::into)
+            .map_err(NormalizeUrlError::UnexpectedError)?
+            .parse()
+    }
+
+    /// Reads the Chromium HSTS preload list in its source format,
+    /// [`transport_security_state_static.json`](https://source.chromium.org/chromium/chromium/src/+/main:net/http/transport_security_state_static.json).
+    ///
+    /// Only entries with the `force-https` mode are kept, e.g. not those which are only pinned.
+    pub fn from_chromium_json(json: &str) -> Result<Self, NormalizeUrlError> {
+        lazy_static! {
+            static ref ENTRY_RE: Regex = Regex::new(r"\{[^{}]*\}").unwrap();
+            static ref NAME_RE: Regex = Regex::new(r#""name"\s*:\s*"([^"]*)""#).unwrap();
+            static ref MODE_RE: Regex = Regex::new(r#""mode"\s*:\s*"force-https""#).unwrap();
+            static ref INCLUDE_SUBDOMAINS_RE: Regex =
+                Regex::new(r#""include_subdomains"\s*:\s*true"#).unwrap();
+        }
+
+        // Comments aren't valid JSON, but the file has plenty of them
+        let json = json
+            .lines()
+            .map(|line| match line.trim_start().starts_with("//") {
+                true => "",
+                false => line,
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let mut hosts = HashMap::new();
+        for entry in ENTRY_RE.find_iter(&json) {
+            let entry = entry
+                .map_err(Into::into)
+                .map_err(NormalizeUrlError::UnexpectedError)?;
+            let line = || json[..entry.start()].matches('\n').count() + 1;
+            let is_match = |regex: &Regex| regex.is_match(entry.as_str()).unwrap_or(false);
+            if !is_match(&MODE_RE) {
+                continue;
+            }
+
+            let Ok(Some(name)) = NAME_RE.captures(entry.as_str()) else {
+                return Err(NormalizeUrlError::InvalidHstsPreloadEntry(line()));
+            };
+            let host = name[1].trim_end_matches('.').to_ascii_lowercase();
+            if host.is_empty() {
+                return Err(NormalizeUrlError::InvalidHstsPreloadEntry(line()));
+            }
+            let entry = hosts.entry(host).or_insert(false);
+            *entry |= is_match(&INCLUDE_SUBDOMAINS_RE);
+        }
+
+        Ok(Self {
+            hosts: Arc::new(hosts),
+        })
+    }
+
+    /// Returns `true` if `host`, or one of its parents listed with `include_subdomains`, is on the list.
+    pub fn contains(&self, host: &str) -> bool {
+        let host = host.trim_end_matches('.').to_ascii_lowercase();
+        if self.hosts.contains_key(&host) {
+            return true;
+        }
+
+        host.match_indices('.').any(|(index, _)| {
+            self.hosts
+                .get(&host[index + 1..])
+                .is_some_and(|include_subdomains| *include_subdomains)
+        })
+    }
+}
+
+impl FromStr for HstsPreloadList {
+    type Err = NormalizeUrlError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut hosts = HashMap::new();
+        for (index, line) in s.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let mut parts = line.split_whitespace();
+            let host = parts
+                .next()
+                .unwrap()
+                .trim_end_matches('.')
+                .to_ascii_lowercase();
+            let include_subdomains = match parts.next() {
+                None => false,
+                Some("include_subdomains") => true,
+                Some(_) => return Err(NormalizeUrlError::InvalidHstsPreloadEntry(index + 1)),
+            };
+            if parts.next().is_some() {
+                return Err(NormalizeUrlError::InvalidHstsPreloadEntry(index + 1));
+            }
+
+            let entry = hosts.entry(host).or_insert(false);
+            *entry |= include_subdomains;
+        }
+
+        Ok(Self {
+            hosts: Arc::new(hosts),
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+/// Controls whether `http` URLs are upgraded to `https` for hosts on an HSTS preload list.
+pub enum HstsPreloadOptions {
+    /// No URLs will be upgraded.
+    None,
+    /// The bundled sample of the Chromium HSTS preload list will be used, see [`HstsPreloadList::sample`].
+    Sample,
+    /// Only hosts on the provided list will be upgraded.
+    List(HstsPreloadList),
+}
@@ -0,0 +1,78 @@
+use idna::uts46::{AsciiDenyList, DnsLength, Hyphens, Uts46};
+
+use crate::NormalizeUrlError;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+/// Controls how hosts are mapped to their ASCII form, see [UTS #46](https://www.unicode.org/reports/tr46/).
+///
+/// The default profile matches the processing of the [WHATWG URL Standard](https://url.spec.whatwg.org/#idna).
+pub struct IdnaProfile {
+    /// Use transitional processing, which maps deviation characters instead of preserving them:
+    /// `ß` → `ss`, `ς` → `σ` and ZWJ/ZWNJ are removed.
+    ///
+    /// Default value: `false`.
+    pub transitional_processing: bool,
+    /// Apply the STD3 ASCII rules, which only allow letters, digits and `-` in ASCII labels.
+    ///
+    /// Default value: `false`.
+    pub use_std3_ascii_rules: bool,
+    /// Reject labels with a `-` in the first, third, fourth or last position.
+    ///
+    /// Default value: `false`.
+    pub check_hyphens: bool,
+    /// Reject hosts whose labels or total length exceed the DNS limits.
+    ///
+    /// Default value: `false`.
+    pub verify_dns_length: bool,
+}
+
+impl IdnaProfile {
+    /// Strict IDNA2008-style validation: STD3 ASCII rules, hyphen checks and DNS length verification,
+    /// with non-transitional processing.
+    pub fn strict() -> Self {
+        Self {
+            transitional_processing: false,
+            use_std3_ascii_rules: true,
+            check_hyphens: true,
+            verify_dns_length: true,
+        }
+    }
+
+    /// Remaps an already ASCII-serialized `host` according to this profile.
+    pub(crate) fn map_host(&self, host: &str) -> Result<String, NormalizeUrlError> {
+        let (unicode, _) =
+            Uts46::new().to_unicode(host.as_bytes(), AsciiDenyList::EMPTY, Hyphens::Allow);
+
+        let mapped = match self.transitional_processing {
+            true => unicode
+                .chars()
+                .filter(|c| !matches!(c, '\u{200C}' | '\u{200D}'))
+                .map(|c| match c {
+                    'ß' => "ss".to_string(),
+                    'ς' => "σ".to_string(),
+                    c => c.to_string(),
+                })
+                .collect::<String>(),
+            false => unicode.into_owned(),
+        };
+
+        Uts46::new()
+            .to_ascii(
+                mapped.as_bytes(),
+                match self.use_std3_ascii_rules {
+                    true => AsciiDenyList::STD3,
+                    false => AsciiDenyList::URL,
+                },
+                match self.check_hyphens {
+                    true => Hyphens::Check,
+                    false => Hyphens::Allow,
+                },
+                match self.verify_dns_length {
+                    true => DnsLength::VerifyAllowRootDot,
+                    false => DnsLength::Ignore,
+                },
+            )
+            .map(|host| host.into_owned())
+            .map_err(|_| NormalizeUrlError::InvalidIdnaHost)
+    }
+}
@@ -0,0 +1,249 @@
+use fancy_regex::Regex;
+use lazy_static::lazy_static;
+use url::{Position, Url};
+
+use crate::{parse_url_string, NormalizeUrlError, Options};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// Controls whether IPFS and IPNS gateway URLs will be canonicalized.
+pub enum IpfsOptions {
+    /// IPFS URLs will be normalized like any other URL.
+    None,
+    /// IPFS URLs will be rewritten to `ipfs://<cid>/path` and `ipns://<name>/path`.
+    Native,
+    /// IPFS URLs will be rewritten to the path-style form of the provided gateway host, e.g. `https://ipfs.io/ipfs/<cid>/path`.
+    Gateway(String),
+}
+
+pub(crate) fn default_ipfs_gateways() -> Vec<String> {
+    [
+        "ipfs.io",
+        "dweb.link",
+        "cloudflare-ipfs.com",
+        "gateway.pinata.cloud",
+        "w3s.link",
+        "nftstorage.link",
+        "4everland.io",
+    ]
+    .into_iter()
+    .map(ToOwned::to_owned)
+    .collect()
+}
+
+const BASE32_ALPHABET: &[u8] = b"abcdefghijklmnopqrstuvwxyz234567";
+const BASE58_ALPHABET: &[u8] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+/// Decodes unpadded RFC 4648 base32 in either case, as used by CIDs and BitTorrent info-hashes.
+pub(crate) fn decode_base32(input: &str) -> Option<Vec<u8>> {
+    let mut output = vec![];
+    let mut buffer = 0u64;
+    let mut bits = 0;
+    for c in input.bytes() {
+        let value = BASE32_ALPHABET
+            .iter()
+            .position(|a| *a == c.to_ascii_lowercase())? as u64;
+        buffer = (buffer << 5) | value;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            output.push((buffer >> bits) as u8);
+        }
+    }
+    Some(output)
+}
+
+fn encode_base32(input: &[u8]) -> String {
+    let mut output = String::new();
+    let mut buffer = 0u64;
+    let mut bits = 0;
+    for byte in input {
+        buffer = (buffer << 8) | *byte as u64;
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            output.push(BASE32_ALPHABET[((buffer >> bits) & 31) as usize] as char);
+        }
+    }
+    if bits > 0 {
+        output.push(BASE32_ALPHABET[((buffer << (5 - bits)) & 31) as usize] as char);
+    }
+    output
+}
+
+fn decode_base58(input: &str) -> Option<Vec<u8>> {
+    let mut output: Vec<u8> = vec![];
+    for c in input.bytes() {
+        let mut carry = BASE58_ALPHABET.iter().position(|a| *a == c)? as u32;
+        for byte in output.iter_mut().rev() {
+            carry += *byte as u32 * 58;
+            *byte = carry as u8;
+            carry >>= 8;
+        }
+        while carry > 0 {
+            output.insert(0, carry as u8);
+            carry >>= 8;
+        }
+    }
+    let leading_zeros = input.bytes().take_while(|c| *c == b'1').count();
+    let mut result = vec![0; leading_zeros];
+    result.extend(output);
+    Some(result)
+}
+
+fn decode_base16(input: &str) -> Option<Vec<u8>> {
+    if !input.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..input.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(input.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+/// Converts a CIDv0, a bare base58btc-encoded SHA-256 multihash of dag-pb content, to CIDv1 in lowercase base32.
+///
+/// Only applies to `/ipfs/` paths: under `/ipns/`, the same encoding is used by legacy peer IDs, which are libp2p keys.
+fn convert_cid_v0(cid: &str) -> Option<String> {
+    if cid.len() != 46 || !cid.starts_with("Qm") {
+        return None;
+    }
+    let mut bytes = vec![0x01, 0x70];
+    bytes.extend(decode_base58(cid)?);
+    Some(format!("b{}", encode_base32(&bytes)))
+}
+
+/// Converts a CIDv1 to lowercase base32, the form used by subdomain gateways.
+///
+/// Returns `None` if `cid` isn't a CIDv1 in a supported multibase encoding.
+fn canonicalize_cid(cid: &str) -> Option<String> {
+    let (multibase, rest) = cid.split_at_checked(1)?;
+    let bytes = match multibase {
+        "b" | "B" => decode_base32(rest)?,
+        "z" => decode_base58(rest)?,
+        "f" | "F" => decode_base16(rest)?,
+        _ => return None,
+    };
+    match bytes.first() {
+        Some(0x01) => Some(format!("b{}", encode_base32(&bytes))),
+        _ => None,
+    }
+}
+
+/// Returns `true` if `id` is a legacy base58btc-encoded peer ID, i.e. a SHA-256 or identity multihash of a libp2p key.
+fn is_peer_id(id: &str) -> bool {
+    matches!(
+        decode_base58(id).as_deref(),
+        Some([0x12, 0x20, hash @ ..]) if hash.len() == 0x20
+    ) || matches!(
+        decode_base58(id).as_deref(),
+        Some([0x00, length, key @ ..]) if key.len() == *length as usize
+    )
+}
+
+/// Returns `true` if `name` is a DNS name with at least two labels, e.g. a DNSLink domain.
+fn is_dns_name(name: &str) -> bool {
+    lazy_static! {
+        static ref RE: Regex =
+            Regex::new(r"(?i)^(?=.{1,253}$)(?:[a-z\d](?:[a-z\d-]{0,61}[a-z\d])?\.)+[a-z\d](?:[a-z\d-]{0,61}[a-z\d])?$")
+                .unwrap();
+    }
+    RE.is_match(name).unwrap_or(false)
+}
+
+/// Decodes a DNSLink name encoded into a single DNS label by subdomain gateways.
+fn decode_dnslink_label(label: &str) -> String {
+    label
+        .split("--")
+        .map(|part| part.replace('-', "."))
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// Rewrites a native IPFS URL, or one of a path-style or subdomain-style gateway from the
+/// `ipfs_gateways` option, to the form selected by the `ipfs` option.
+///
+/// Returns `None` if `url_obj` isn't an IPFS or IPNS URL.
+pub(crate) fn canonicalize_ipfs_url(
+    url_obj: &Url,
+    options: &Options,
+) -> Result<Option<Url>, NormalizeUrlError> {
+    lazy_static! {
+        static ref SUBDOMAIN_RE: Regex = Regex::new(r"^([^.]+)\.(ipfs|ipns)\.(.+)$").unwrap();
+        static ref PATH_RE: Regex = Regex::new(r"^\/(ipfs|ipns)\/([^\/]+)(.*)$").unwrap();
+    }
+
+    if options.ipfs == IpfsOptions::None {
+        return Ok(None);
+    }
+
+    let Some(host) = url_obj.host_str() else {
+        return Ok(None);
+    };
+    let host = host.trim_end_matches('.').to_lowercase();
+    let is_gateway = |host: &str| {
+        options
+            .ipfs_gateways
+            .iter()
+            .any(|gateway| gateway.eq_ignore_ascii_case(host))
+    };
+
+    let (namespace, id, path) = match url_obj.scheme() {
+        "ipfs" | "ipns" => (
+            url_obj.scheme().to_owned(),
+            url_obj.host_str().unwrap().to_owned(),
+            url_obj.path().to_owned(),
+        ),
+        "http" | "https" => {
+            let subdomain = SUBDOMAIN_RE
+                .captures(&host)
+                .map_err(Into::into)
+                .map_err(NormalizeUrlError::UnexpectedError)?;
+            let path = PATH_RE
+                .captures(url_obj.path())
+                .map_err(Into::into)
+                .map_err(NormalizeUrlError::UnexpectedError)?;
+            match (subdomain, path) {
+                (Some(captures), _) if is_gateway(&captures[3]) => {
+                    let id = match &captures[2] {
+                        "ipns" => decode_dnslink_label(&captures[1]),
+                        _ => captures[1].to_owned(),
+                    };
+                    (captures[2].to_owned(), id, url_obj.path().to_owned())
+                }
+                (_, Some(captures)) if is_gateway(&host) => (
+                    captures[1].to_owned(),
+                    captures[2].to_owned(),
+                    captures[3].to_owned(),
+                ),
+                _ => return Ok(None),
+            }
+        }
+        _ => return Ok(None),
+    };
+
+    // IDs which aren't recognized are left untouched, as they may be case-sensitive or not even valid hosts
+    let id = match namespace.as_str() {
+        "ipfs" => convert_cid_v0(&id).or_else(|| canonicalize_cid(&id)),
+        // IPNS names are DNSLink domain names, CIDs of libp2p keys, or case-sensitive peer IDs, kept as they are
+        _ if is_dns_name(&id) => Some(id.to_lowercase()),
+        _ if is_peer_id(&id) => Some(id),
+        _ => canonicalize_cid(&id),
+    };
+    let Some(id) = id else {
+        return Ok(None);
+    };
+
+    let url_string = match &options.ipfs {
+        IpfsOptions::Gateway(gateway) => {
+            format!("https://{}/{}/{}{}", gateway, namespace, id, path)
+        }
+        _ => format!("{}://{}{}", namespace, id, path),
+    };
+
+    parse_url_string(&format!(
+        "{}{}",
+        url_string,
+        &url_obj[Position::AfterPath..]
+    ))
+    .map(Some)
+}
@@ -22,20 +22,63 @@ assert_eq!(result.unwrap(), "https://rust-lang.org")
 use derive_builder::Builder;
 use fancy_regex::Regex;
 use lazy_static::lazy_static;
+use std::collections::HashMap;
 use std::iter::Peekable;
 use thiserror::Error;
 use url::Url;
 use urlencoding::decode;
 
+mod auth;
+mod compare;
+mod defang;
+mod extract;
+mod file;
+mod fingerprint;
+mod git;
+mod homograph;
+mod hsts;
+mod idna_profile;
+mod ipfs;
+mod magnet;
+mod mailto;
+mod pattern;
+mod relative;
+mod sanitize;
+mod scheme;
+mod storage;
+mod surt;
+mod template;
+mod wrapper;
+
+pub use auth::{detect_credentials, Credentials, StripAuthenticationOptions};
+pub use compare::{urls_equivalent, ComparisonLevel, UrlComparison, UrlComponent};
+pub use defang::{defang, refang};
+pub use extract::{extract_url, normalize_url_lenient, ExtractedUrl, Removed};
+pub use file::normalize_file_url;
+pub use fingerprint::{
+    component_fingerprints, fingerprint, ComponentFingerprints, Fingerprint, FingerprintVersion,
+};
+pub use git::{normalize_git_remote, GitRemoteOptions, GitRemoteOptionsBuilder};
+pub use homograph::{analyze_host, HostAnalysis, LabelAnalysis};
+pub use hsts::{HstsPreloadList, HstsPreloadOptions};
+pub use idna_profile::IdnaProfile;
+pub use ipfs::IpfsOptions;
+pub use pattern::{UrlPattern, UrlPatternComponentResult, UrlPatternInit, UrlPatternResult};
+pub use relative::make_relative;
+pub use sanitize::{sanitize_url, LinkVerdict, SanitizeOptions, SanitizeOptionsBuilder};
+pub use storage::ObjectStorageOptions;
+pub use surt::{from_surt, to_surt};
+pub use template::{
+    default_detectors, template_url, Detector, TemplateOptions, TemplateOptionsBuilder,
+};
+pub use wrapper::WrapperSchemeOptions;
+
 struct SkipLastIterator<I: Iterator>(Peekable<I>);
 impl<I: Iterator> Iterator for SkipLastIterator<I> {
     type Item = I::Item;
     fn next(&mut self) -> Option<Self::Item> {
         let item = self.0.next();
-        match self.0.peek() {
-            Some(_) => Some(item.unwrap()),
-            None => None,
-        }
+        self.0.peek().map(|_| item.unwrap())
     }
 }
 trait SkipLast: Iterator + Sized {
@@ -67,10 +110,71 @@ pub enum RemoveDirectoryIndexOptions {
     List(Vec<Regex>),
 }
 
+impl RemoveDirectoryIndexOptions {
+    /// Returns the regexes matching the directory indices to remove.
+    fn regexs(&self) -> Vec<Regex> {
+        match self {
+            RemoveDirectoryIndexOptions::None => vec![],
+            RemoveDirectoryIndexOptions::Default => vec![Regex::new(r"^index\.[a-z]+$").unwrap()],
+            RemoveDirectoryIndexOptions::List(regexs) => regexs.to_vec(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Controls which explicit port numbers will be removed.
+pub enum PortPolicy {
+    /// Only ports matching the default port of the scheme (see `Options::default_ports`) will be removed.
+    RemoveDefault,
+    /// All ports will be removed.
+    RemoveAll,
+    /// All ports will be kept, except default ports of special schemes, which the URL parser always removes.
+    Keep,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+/// Version of the normalization rules.
+///
+/// The output of a released version is frozen and covered by golden corpora of representative options in `tests/golden/`, so normalized URLs
+/// can be stored, e.g. as database keys. Behavior changes only ship under a new version, so keys can be migrated deliberately.
+pub enum NormalizationVersion {
+    /// Initial rules.
+    V1,
+}
+
+impl NormalizationVersion {
+    /// The latest version.
+    pub const LATEST: NormalizationVersion = NormalizationVersion::V1;
+}
+
+fn default_port_table() -> HashMap<String, u16> {
+    [
+        ("http", 80),
+        ("https", 443),
+        ("ws", 80),
+        ("wss", 443),
+        ("ftp", 21),
+        ("ssh", 22),
+        ("git", 9418),
+        ("redis", 6379),
+        ("postgres", 5432),
+        ("amqp", 5672),
+    ]
+    .into_iter()
+    .map(|(scheme, port)| (scheme.to_string(), port))
+    .collect()
+}
+
 #[derive(Builder, Debug, Clone)]
-#[builder(setter(into))]
+#[builder(setter(into), build_fn(validate = "Self::validate"))]
 /// Normalization options.
 pub struct Options {
+    /// Version of the normalization rules.
+    ///
+    /// Default value: `NormalizationVersion::V1`.
+    #[builder(default = "NormalizationVersion::V1")]
+    pub version: NormalizationVersion,
     #[builder(default = "\"http\".to_string()")]
     /// Default protocol.
     ///
@@ -83,21 +187,43 @@ pub struct Options {
     pub normalize_protocol: bool,
     /// Normalize HTTPS to HTTP.
     ///
+    /// Shorthand for the `https` → `http` rule of `scheme_mapping`.
+    ///
     /// Default value: `false`.
     #[builder(default = "false")]
     pub force_http: bool,
     /// Normalize HTTP to HTTPS.
     ///
+    /// Shorthand for the `http` → `https` rule of `scheme_mapping`.
+    ///
     /// This option cannot be used with the `force_http` option at the same time.
     ///
     /// Default value: `false`.
     #[builder(default = "false")]
     pub force_https: bool,
+    /// Replaces schemes of the URL, e.g. `{"ws": "wss", "ftp": "ftps"}`.
+    ///
+    /// A scheme cannot be mapped to two different schemes (including through `force_http` and `force_https`),
+    /// and the target of a rule cannot be the source of another one. `OptionsBuilder::build` rejects conflicting rules.
+    ///
+    /// Default value: `{}`.
+    #[builder(default = "HashMap::new()")]
+    pub scheme_mapping: HashMap<String, String>,
+    /// Normalize HTTP to HTTPS only for hosts on an HSTS preload list, including subdomains of hosts listed with `include_subdomains`.
+    ///
+    /// Has no effect when HTTPS is mapped to another scheme, e.g. by the `force_http` option.
+    ///
+    /// Default value: `None`.
+    #[builder(default = "HstsPreloadOptions::None")]
+    pub hsts_preload: HstsPreloadOptions,
     /// Strip the authentication part of the URL.
     ///
-    /// Default value: `true`.
-    #[builder(default = "true")]
-    pub strip_authentication: bool,
+    /// `true` and `false` can be used in place of `StripAuthenticationOptions::All` and `StripAuthenticationOptions::None`
+    /// with the builder. Before 0.3 this field was a `bool`.
+    ///
+    /// Default value: `All`.
+    #[builder(default = "StripAuthenticationOptions::All")]
+    pub strip_authentication: StripAuthenticationOptions,
     /// Strip the hash part of the URL.
     ///
     /// Default value: `false`.
@@ -157,14 +283,93 @@ pub struct Options {
     ///
     /// Port 443 is always removed from HTTPS URLs and 80 is always removed from HTTP URLs regardless of this option.
     ///
+    /// Shorthand for `port_policy` set to `PortPolicy::RemoveAll`.
+    ///
     /// Default value: `false`.
     #[builder(default = "false")]
     pub remove_explicit_port: bool,
+    /// Controls which explicit port numbers will be removed.
+    ///
+    /// Default value: `PortPolicy::RemoveDefault`.
+    #[builder(default = "PortPolicy::RemoveDefault")]
+    pub port_policy: PortPolicy,
+    /// Default port of each scheme, used by `PortPolicy::RemoveDefault`.
+    ///
+    /// Default value: `http` 80, `https` 443, `ws` 80, `wss` 443, `ftp` 21, `ssh` 22, `git` 9418, `redis` 6379, `postgres` 5432, `amqp` 5672.
+    #[builder(default = "default_port_table()")]
+    pub default_ports: HashMap<String, u16>,
     /// Sorts the query parameters alphabetically by key.
     ///
+    /// Header fields of `mailto:` URLs are sorted by name as well.
+    ///
     /// Default value: `true`.
     #[builder(default = "true")]
     pub sort_query_parameters: bool,
+    /// Sorts and deduplicates the recipients of `mailto:` URLs.
+    ///
+    /// Default value: `false`.
+    #[builder(default = "false")]
+    pub sort_mailto_recipients: bool,
+    /// Canonicalizes URLs referencing objects in Amazon S3, Google Cloud Storage or Azure Blob Storage,
+    /// e.g. `https://bucket.s3.us-east-1.amazonaws.com/key` → `s3://bucket/key`.
+    ///
+    /// Pre-signed URL and SAS token parameters are removed. The object key is kept verbatim apart from its percent-encoding.
+    ///
+    /// Default value: `None`.
+    #[builder(default = "ObjectStorageOptions::None")]
+    pub object_storage: ObjectStorageOptions,
+    /// Canonicalizes IPFS and IPNS URLs: native `ipfs://<cid>/path` URLs, and URLs of path-style (`https://ipfs.io/ipfs/<cid>/path`)
+    /// or subdomain-style (`https://<cid>.ipfs.dweb.link/path`) gateways listed in `ipfs_gateways`.
+    ///
+    /// CIDs are converted to CIDv1 in lowercase base32, so CIDv0 and CIDv1 of the same content are equal.
+    /// IPNS names which aren't CIDv1, e.g. peer IDs, are kept as they are, except DNSLink domain names which are lowercased.
+    ///
+    /// Default value: `None`.
+    #[builder(default = "IpfsOptions::None")]
+    pub ipfs: IpfsOptions,
+    /// Hosts of the IPFS gateways recognized by the `ipfs` option.
+    ///
+    /// Default value: `ipfs.io`, `dweb.link`, `cloudflare-ipfs.com`, `gateway.pinata.cloud`, `w3s.link`, `nftstorage.link`, `4everland.io`.
+    #[builder(default = "ipfs::default_ipfs_gateways()")]
+    pub ipfs_gateways: Vec<String>,
+    /// IDNA processing applied to the host.
+    ///
+    /// Default value: `IdnaProfile::default()`, which matches the WHATWG URL Standard.
+    #[builder(default = "IdnaProfile::default()")]
+    pub idna_profile: IdnaProfile,
+    /// Keeps the scheme in the output of [`to_surt`], e.g. `https://(com,example,)/path` instead of `com,example)/path`.
+    ///
+    /// Default value: `false`.
+    #[builder(default = "false")]
+    pub surt_include_scheme: bool,
+    /// Reverses common defanging conventions before normalization, e.g. `hxxp://evil[.]com` → `http://evil.com`. See [`refang`].
+    ///
+    /// Default value: `false`.
+    #[builder(default = "false")]
+    pub refang: bool,
+    /// Controls how URLs wrapped in `view-source:`, `blob:`, `jar:` or `feed:` will be normalized.
+    ///
+    /// The inner URL is normalized with the same options, except that `strip_protocol` only applies when the wrapper is stripped.
+    ///
+    /// Default value: `WrapperSchemeOptions::Keep`.
+    #[builder(default = "WrapperSchemeOptions::Keep")]
+    pub wrapper_schemes: WrapperSchemeOptions,
+}
+
+impl OptionsBuilder {
+    /// Rejects conflicting `scheme_mapping` rules, see [`NormalizeUrlError::ConflictingSchemeMapping`].
+    fn validate(&self) -> Result<(), String> {
+        let result = scheme::resolve_scheme_rules(
+            &self.scheme_mapping.clone().unwrap_or_default(),
+            self.force_http.unwrap_or(false),
+            self.force_https.unwrap_or(false),
+        );
+        // `force_http` and `force_https` together are reported by `normalize_url`
+        match result {
+            Err(err @ NormalizeUrlError::ConflictingSchemeMapping(_)) => Err(err.to_string()),
+            _ => Ok(()),
+        }
+    }
 }
 
 #[derive(Error, Debug)]
@@ -172,18 +377,234 @@ pub struct Options {
 pub enum NormalizeUrlError {
     #[error("The `forceHttp` and `forceHttps` options cannot be used together")]
     ForceHttpAndHttpAreExclusive,
+    #[error("Conflicting `scheme_mapping` rules for the `{0}` scheme")]
+    ConflictingSchemeMapping(String),
     #[error("Unexpected error returned by `Url` library")]
     URLError,
+    #[error("The host is not a valid IDNA domain name")]
+    InvalidIdnaHost,
+    #[error("Invalid HSTS preload list entry on line {0}")]
+    InvalidHstsPreloadEntry(usize),
+    #[error("Relative paths cannot be converted to a `file:` URL")]
+    RelativeFilePath,
+    #[error("Invalid URL pattern: {0}")]
+    InvalidUrlPattern(String),
+    #[error("Local paths and `file:` URLs aren't git remotes")]
+    LocalGitRemote,
     #[error("Unexpected error")]
     UnexpectedError(#[from] anyhow::Error),
 }
 
+/// Returns `true` if the query parameter `key` passes the `remove_query_parameters` and `keep_query_parameters` options.
+fn is_query_parameter_kept(key: &str, options: &Options) -> Result<bool, NormalizeUrlError> {
+    let matches_any = |regexs: &[Regex]| -> Result<bool, NormalizeUrlError> {
+        for regex in regexs {
+            if regex
+                .is_match(key)
+                .map_err(Into::into)
+                .map_err(NormalizeUrlError::UnexpectedError)?
+            {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    };
+
+    let removed = match &options.remove_query_parameters {
+        RemoveQueryParametersOptions::None => false,
+        RemoveQueryParametersOptions::All => options.keep_query_parameters.is_none(),
+        RemoveQueryParametersOptions::List(regexs) => matches_any(regexs)?,
+    };
+
+    Ok(!removed
+        && match &options.keep_query_parameters {
+            Some(regexs) => matches_any(regexs)?,
+            None => true,
+        })
+}
+
+/// Returns `true` for relative references other than protocol-relative ones, e.g. `./foo`, `../foo` and `/foo`.
+fn is_relative_url(url_string: &str) -> Result<bool, NormalizeUrlError> {
+    lazy_static! {
+        static ref RE: Regex = Regex::new(r"^\.*\/").unwrap();
+    }
+    Ok(!url_string.starts_with("//")
+        && RE
+            .is_match(url_string)
+            .map_err(Into::into)
+            .map_err(NormalizeUrlError::UnexpectedError)?)
+}
+
+/// Prepends `default_protocol` to a trimmed URL string, unless it's a relative URL.
+///
+/// Returns the resulting string and whether the URL was protocol-relative.
+fn prepare_url_string(
+    url_string: &str,
+    options: &Options,
+) -> Result<(String, bool), NormalizeUrlError> {
+    let has_relative_protocol = url_string.starts_with("//");
+
+    // Prepend protocol
+    if is_relative_url(url_string)? {
+        return Ok((url_string.to_owned(), has_relative_protocol));
+    }
+
+    lazy_static! {
+        static ref RE: Regex = Regex::new(r"^(?!(?:\w+:)?\/\/)|^\/\/").unwrap();
+    }
+    let url_string = RE
+        .replace(url_string, format!("{}://", options.default_protocol))
+        .to_string();
+
+    Ok((url_string, has_relative_protocol))
+}
+
+fn parse_error(err: url::ParseError) -> NormalizeUrlError {
+    match err {
+        url::ParseError::IdnaError => NormalizeUrlError::InvalidIdnaHost,
+        err => NormalizeUrlError::UnexpectedError(err.into()),
+    }
+}
+
+fn parse_url_string(url_string: &str) -> Result<Url, NormalizeUrlError> {
+    Url::parse(url_string).map_err(parse_error)
+}
+
+/// Parses `url` the same way as [`normalize_url`], without normalizing it.
+pub(crate) fn parse_url(url: &str, options: &Options) -> Result<Url, NormalizeUrlError> {
+    let (url_string, _) = prepare_url_string(url.trim(), options)?;
+    parse_url_string(&url_string)
+}
+
+/// Normalizes `url` with [`normalize_url`], keeping its protocol, then parses the result.
+///
+/// Returns the normalized URL, and the parsed URL unless it's a relative reference. Protocol-relative URLs, kept by
+/// `normalize_protocol(false)`, are parsed with `default_protocol`.
+pub(crate) fn normalize_and_parse_url(
+    url: &str,
+    options: &Options,
+) -> Result<(String, Option<Url>), NormalizeUrlError> {
+    // The scheme is needed to parse the normalized URL
+    let normalized = normalize_url(
+        url,
+        &Options {
+            strip_protocol: false,
+            ..options.clone()
+        },
+    )?;
+
+    let url_obj = if is_relative_url(&normalized)? {
+        None
+    } else if normalized.starts_with("//") {
+        Some(parse_url_string(&format!(
+            "{}:{}",
+            options.default_protocol, normalized
+        ))?)
+    } else {
+        Some(parse_url_string(&normalized)?)
+    };
+    Ok((normalized, url_obj))
+}
+
+/// Removes duplicate slashes from `path`, except those preceded by a protocol, then decodes its URI octets.
+pub(crate) fn clean_path(path: &str) -> Result<String, NormalizeUrlError> {
+    // Split the string by occurrences of this protocol regex, and perform
+    // duplicate-slash replacement on the strings between those occurrences
+    // (if any).
+    lazy_static! {
+        static ref RE: Regex = Regex::new(r"\b[a-z][a-z\d+\-.]{1,50}:\/\/").unwrap();
+        static ref RE2: Regex = Regex::new(r"\/{2,}").unwrap();
+    }
+
+    let mut last_index = 0;
+    let mut result = "".to_string();
+    for re_match in RE.captures_iter(path) {
+        let re_match = re_match
+            .map_err(Into::into)
+            .map_err(NormalizeUrlError::UnexpectedError)?;
+
+        let protocol = re_match.get(0).unwrap();
+        let protocol_at_index = protocol.start();
+        let intermediate = &path[last_index..protocol_at_index];
+
+        result += &RE2.replace_all(intermediate, "/");
+        result += protocol.as_str();
+        last_index = protocol_at_index + protocol.as_str().len();
+    }
+
+    let remnant = &path[last_index..];
+    result += &RE2.replace_all(remnant, "/");
+
+    Ok(decode(&result)
+        .unwrap_or(std::borrow::Cow::Borrowed(&result))
+        .into_owned())
+}
+
 pub fn normalize_url(url: &str, options: &Options) -> Result<String, NormalizeUrlError> {
-    if options.force_http && options.force_https {
-        return Err(NormalizeUrlError::ForceHttpAndHttpAreExclusive);
+    match options.version {
+        NormalizationVersion::V1 => normalize_url_v1(url, options),
     }
+}
+
+/// Normalizes `url` with the `V1` rules. Their output is frozen, see [`NormalizationVersion`].
+fn normalize_url_v1(url: &str, options: &Options) -> Result<String, NormalizeUrlError> {
+    let scheme_mapping = scheme::resolve_scheme_mapping(options)?;
 
-    let mut url_string = url.trim().to_owned();
+    let refanged;
+    let url_string = match options.refang {
+        true => {
+            refanged = refang(url);
+            refanged.as_str()
+        }
+        false => url.trim(),
+    };
+
+    // Wrapped URL
+    if let Some(url_string) = wrapper::normalize_wrapped_url(url_string, options)? {
+        return Ok(url_string);
+    }
+
+    // Mailto URL
+    {
+        lazy_static! {
+            static ref RE: Regex = Regex::new(r"(?i)^mailto:").unwrap();
+        }
+        if RE
+            .is_match(url_string)
+            .map_err(Into::into)
+            .map_err(NormalizeUrlError::UnexpectedError)?
+        {
+            return mailto::normalize_mailto_url(url_string, options);
+        }
+    }
+
+    // File URL
+    {
+        lazy_static! {
+            static ref RE: Regex = Regex::new(r"(?i)^file:").unwrap();
+        }
+        if RE
+            .is_match(url_string)
+            .map_err(Into::into)
+            .map_err(NormalizeUrlError::UnexpectedError)?
+        {
+            return normalize_file_url(url_string, options);
+        }
+    }
+
+    // Magnet URI
+    {
+        lazy_static! {
+            static ref RE: Regex = Regex::new(r"(?i)^magnet:").unwrap();
+        }
+        if RE
+            .is_match(url_string)
+            .map_err(Into::into)
+            .map_err(NormalizeUrlError::UnexpectedError)?
+        {
+            return magnet::normalize_magnet_url(url_string, options);
+        }
+    }
 
     // Data URL
     //if (/^data:/i.test(urlString)) {
@@ -195,52 +616,54 @@ pub fn normalize_url(url: &str, options: &Options) -> Result<String, NormalizeUr
     //}
     //
 
-    let has_relative_protocol = url_string.starts_with("//");
-    let is_relative_url = !has_relative_protocol && {
-        lazy_static! {
-            static ref RE: Regex = Regex::new(r"^\.*\/").unwrap();
-        }
-        RE.is_match(&url_string)
-            .map_err(Into::into)
-            .map_err(NormalizeUrlError::UnexpectedError)?
-    };
+    // Relative reference
+    if is_relative_url(url_string)? {
+        return relative::normalize_relative_reference(url_string, options);
+    }
 
-    // Prepend protocol
-    if !is_relative_url {
-        lazy_static! {
-            static ref RE: Regex = Regex::new(r"^(?!(?:\w+:)?\/\/)|^\/\/").unwrap();
-        }
-        url_string = RE
-            .replace(&url_string, format!("{}://", options.default_protocol))
-            .to_string();
+    let (mut url_string, has_relative_protocol) = prepare_url_string(url_string, options)?;
+    let mut url_obj = parse_url_string(&url_string)?;
+
+    if let Some(url_string) = storage::canonicalize_storage_url(&url_obj, options)? {
+        return Ok(url_string);
     }
 
-    let mut url_obj = Url::parse(&url_string)
-        .map_err(Into::into)
-        .map_err(NormalizeUrlError::UnexpectedError)?;
+    if let Some(ipfs_url) = ipfs::canonicalize_ipfs_url(&url_obj, options)? {
+        url_obj = ipfs_url;
+    }
 
-    if options.force_http && url_obj.scheme() == "https" {
-        url_obj
-            .set_scheme("http")
-            .map_err(|()| NormalizeUrlError::URLError)?;
+    if options.idna_profile != IdnaProfile::default() && url_obj.is_special() {
+        if let Some(url::Host::Domain(host)) = url_obj.host() {
+            let host = options.idna_profile.map_host(host)?;
+            url_obj
+                .set_host(Some(&host))
+                .map_err(Into::into)
+                .map_err(NormalizeUrlError::UnexpectedError)?;
+        }
     }
 
-    if options.force_https && url_obj.scheme() == "http" {
-        url_obj
-            .set_scheme("https")
-            .map_err(|()| NormalizeUrlError::URLError)?;
+    if let Some(scheme) = scheme_mapping.get(url_obj.scheme()) {
+        scheme::replace_scheme(&mut url_obj, scheme)?;
     }
 
-    // Remove auth
-    if options.strip_authentication {
-        url_obj
-            .set_username("")
-            .map_err(|()| NormalizeUrlError::URLError)?;
-        url_obj
-            .set_password(None)
-            .map_err(|()| NormalizeUrlError::URLError)?;
+    if !scheme_mapping.contains_key("https") && url_obj.scheme() == "http" {
+        let host = url_obj.host_str().unwrap_or_default();
+        let is_preloaded = match &options.hsts_preload {
+            HstsPreloadOptions::None => false,
+            HstsPreloadOptions::Sample => HstsPreloadList::sample().contains(host),
+            HstsPreloadOptions::List(list) => list.contains(host),
+        };
+
+        if is_preloaded {
+            url_obj
+                .set_scheme("https")
+                .map_err(|()| NormalizeUrlError::URLError)?;
+        }
     }
 
+    // Remove auth
+    auth::strip_authentication(&mut url_obj, options.strip_authentication)?;
+
     // Remove hash
     if options.strip_hash {
         url_obj.set_fragment(None);
@@ -255,53 +678,16 @@ pub fn normalize_url(url: &str, options: &Options) -> Result<String, NormalizeUr
         });
     }
 
-    // Remove duplicate slashes if not preceded by a protocol
-    if url_obj.path().len() > 0 {
-        // Split the string by occurrences of this protocol regex, and perform
-        // duplicate-slash replacement on the strings between those occurrences
-        // (if any).
-        lazy_static! {
-            static ref RE: Regex = Regex::new(r"\b[a-z][a-z\d+\-.]{1,50}:\/\/").unwrap();
-            static ref RE2: Regex = Regex::new(r"\/{2,}").unwrap();
-        }
-
-        let mut last_index = 0;
-        let mut result = "".to_string();
-        for re_match in RE.captures_iter(url_obj.path()) {
-            let re_match = re_match
-                .map_err(Into::into)
-                .map_err(NormalizeUrlError::UnexpectedError)?;
-
-            let protocol = re_match.get(0).unwrap();
-            let protocol_at_index = protocol.start();
-            let intermediate = &url_obj.path()[last_index..protocol_at_index];
-
-            result += &RE2.replace_all(intermediate, "/");
-            result += protocol.as_str();
-            last_index = protocol_at_index + protocol.as_str().len();
-        }
-
-        let remnant = &url_obj.path()[last_index..];
-        result += &RE2.replace_all(remnant, "/");
-
-        url_obj.set_path(&result);
-    }
-
-    // Decode URI octets
+    // Remove duplicate slashes and decode URI octets
     if !url_obj.path().is_empty() {
-        let decoded_path =
-            decode(url_obj.path()).unwrap_or(std::borrow::Cow::Borrowed(url_obj.path()));
-        url_obj.set_path(&decoded_path.to_string());
+        let path = clean_path(url_obj.path())?;
+        url_obj.set_path(&path);
     }
 
     // Remove directory index
-    let remove_directory_index_regexs = match &options.remove_directory_index {
-        RemoveDirectoryIndexOptions::None => vec![],
-        RemoveDirectoryIndexOptions::Default => vec![Regex::new(r"^index\.[a-z]+$").unwrap()],
-        RemoveDirectoryIndexOptions::List(regexs) => regexs.to_vec(),
-    };
+    let remove_directory_index_regexs = options.remove_directory_index.regexs();
 
-    if remove_directory_index_regexs.len() > 0 && url_obj.path_segments().is_some() {
+    if !remove_directory_index_regexs.is_empty() && url_obj.path_segments().is_some() {
         let mut matched = false;
         let path_segments = url_obj
             .path_segments()
@@ -329,7 +715,6 @@ pub fn normalize_url(url: &str, options: &Options) -> Result<String, NormalizeUr
 
         url_obj
             .path_segments_mut()
-            .map_err(Into::into)
             .map_err(|()| NormalizeUrlError::URLError)?
             .clear()
             .extend(&it);
@@ -344,10 +729,9 @@ pub fn normalize_url(url: &str, options: &Options) -> Result<String, NormalizeUr
             static ref RE: Regex = Regex::new(r"\.$").unwrap();
         }
         // Remove trailing dot
+        let host_str = RE.replace(url_obj.host_str().unwrap(), "").into_owned();
         url_obj
-            .set_host(Some(
-                &RE.replace(url_obj.host_str().unwrap(), "").to_string(),
-            ))
+            .set_host(Some(&host_str))
             .map_err(Into::into)
             .map_err(NormalizeUrlError::UnexpectedError)?;
 
@@ -362,7 +746,8 @@ pub fn normalize_url(url: &str, options: &Options) -> Result<String, NormalizeUr
             // Source: https://en.wikipedia.org/wiki/Hostname#Restrictions_on_valid_host_names
             // Each TLD should be up to 63 characters long (min: 2).
             // It is technically possible to have a single character TLD, but none currently exist.
-            let host_str = url_obj.host_str().unwrap().to_string();
+            // The host may be gone once its trailing dot is removed, e.g. `foo://a@./`
+            let host_str = url_obj.host_str().unwrap_or_default().to_string();
             if RE
                 .is_match(&host_str)
                 .map_err(Into::into)
@@ -410,12 +795,12 @@ pub fn normalize_url(url: &str, options: &Options) -> Result<String, NormalizeUr
     }
 
     // Keep wanted query parameters
-    if options.keep_query_parameters.is_some() {
+    if let Some(keep_query_parameters) = &options.keep_query_parameters {
         let url_copy = url_obj.clone();
         let mut query_pairs = url_obj.query_pairs_mut();
         query_pairs.clear();
         for (key, value) in url_copy.query_pairs() {
-            for regex in options.keep_query_parameters.as_ref().unwrap() {
+            for regex in keep_query_parameters {
                 if regex
                     .is_match(&key)
                     .map_err(Into::into)
@@ -447,8 +832,10 @@ pub fn normalize_url(url: &str, options: &Options) -> Result<String, NormalizeUr
         }
 
         if let Some(query) = url_obj.query() {
-            let decoded_query = decode(query).unwrap_or(std::borrow::Cow::Borrowed(query));
-            url_obj.set_query(Some(&decoded_query.to_string()));
+            let decoded_query = decode(query)
+                .unwrap_or(std::borrow::Cow::Borrowed(query))
+                .into_owned();
+            url_obj.set_query(Some(&decoded_query));
         }
     }
 
@@ -456,27 +843,34 @@ pub fn normalize_url(url: &str, options: &Options) -> Result<String, NormalizeUr
         lazy_static! {
             static ref RE: Regex = Regex::new(r"\/$").unwrap();
         }
-        url_obj.set_path(&RE.replace(url_obj.path(), "").to_string());
+        let path = RE.replace(url_obj.path(), "").into_owned();
+        url_obj.set_path(&path);
     }
 
     // Remove an explicit port number, excluding a default port number, if applicable
-    if options.remove_explicit_port && url_obj.port().is_some() {
-        url_obj
-            .set_port(None)
-            .map_err(|()| NormalizeUrlError::URLError)?;
+    if let Some(port) = url_obj.port() {
+        let port_policy = match options.remove_explicit_port {
+            true => PortPolicy::RemoveAll,
+            false => options.port_policy,
+        };
+        let remove = match port_policy {
+            PortPolicy::RemoveDefault => options.default_ports.get(url_obj.scheme()) == Some(&port),
+            PortPolicy::RemoveAll => true,
+            PortPolicy::Keep => false,
+        };
+
+        if remove {
+            url_obj
+                .set_port(None)
+                .map_err(|()| NormalizeUrlError::URLError)?;
+        }
     }
 
     let old_url_string = url_string;
 
     url_string = url_obj.to_string();
 
-    let is_option_empty = |x: Option<&str>| -> bool {
-        match x {
-            Some("") => true,
-            None => true,
-            _ => false,
-        }
-    };
+    let is_option_empty = |x: Option<&str>| -> bool { matches!(x, Some("") | None) };
 
     if !options.remove_single_slash
         && url_obj.path() == "/"
@@ -518,3 +912,30 @@ pub fn normalize_url(url: &str, options: &Options) -> Result<String, NormalizeUr
 
     Ok(url_string)
 }
+
+/// Resolves `url` against `base` per the WHATWG URL Standard, then normalizes it with [`normalize_url`].
+///
+/// Protocol-relative links take the scheme of `base`, e.g. `//cdn.example.com/a.js` against `https://example.com/`
+/// becomes `https://cdn.example.com/a.js`. `base` itself is parsed like the input of [`normalize_url`], so it may lack a protocol.
+///
+/// ```
+/// use normalize_url_rs::{normalize_url_with_base, OptionsBuilder};
+///
+/// let options = OptionsBuilder::default().build().unwrap();
+/// let result = normalize_url_with_base("../img/./a.png", "https://www.example.com/blog/post/", &options);
+///
+/// assert_eq!(result.unwrap(), "https://example.com/blog/img/a.png")
+/// ```
+pub fn normalize_url_with_base(
+    url: &str,
+    base: &str,
+    options: &Options,
+) -> Result<String, NormalizeUrlError> {
+    let url = match options.refang {
+        true => refang(url),
+        false => url.trim().to_owned(),
+    };
+    let base_obj = parse_url(base, options)?;
+    let url_obj = base_obj.join(&url).map_err(parse_error)?;
+    normalize_url(url_obj.as_str(), options)
+}
@@ -0,0 +1,113 @@
+use fancy_regex::Regex;
+use lazy_static::lazy_static;
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use url::form_urlencoded;
+
+use crate::ipfs::decode_base32;
+use crate::{is_query_parameter_kept, NormalizeUrlError, Options};
+
+/// Characters encoded in parameter values: everything except `unreserved`, `:`, `/` and `@`.
+const MAGNET_ENCODE_SET: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'_')
+    .remove(b'~')
+    .remove(b':')
+    .remove(b'/')
+    .remove(b'@');
+
+/// Normalizes an exact topic: BitTorrent info-hashes are converted to lowercase hex.
+fn normalize_exact_topic(value: &str) -> String {
+    lazy_static! {
+        static ref RE: Regex = Regex::new(r"(?i)^urn:(btih|btmh):(.+)$").unwrap();
+        static ref HEX_RE: Regex = Regex::new(r"^[\da-fA-F]+$").unwrap();
+        static ref BASE32_RE: Regex = Regex::new(r"^[a-zA-Z2-7]{32}$").unwrap();
+    }
+
+    let Ok(Some(captures)) = RE.captures(value) else {
+        return match value.split_once(':') {
+            Some((urn, rest)) => format!("{}:{}", urn.to_lowercase(), rest),
+            None => value.to_owned(),
+        };
+    };
+
+    let namespace = captures[1].to_lowercase();
+    let hash = &captures[2];
+    let is_hex = HEX_RE.is_match(hash).unwrap_or(false);
+    // BitTorrent v1 info-hashes are 40 hex digits or 32 base32 characters, v2 multihashes are hex of any length
+    let hash = if is_hex && (namespace == "btmh" || hash.len() == 40) {
+        hash.to_lowercase()
+    } else if namespace == "btih" && BASE32_RE.is_match(hash).unwrap_or(false) {
+        decode_base32(hash)
+            .unwrap()
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect()
+    } else {
+        hash.to_owned()
+    };
+
+    format!("urn:{}:{}", namespace, hash)
+}
+
+/// Normalizes a `magnet:` URI.
+///
+/// Parameter names are lowercased, BitTorrent info-hashes (`xt=urn:btih:`) are converted to lowercase hex,
+/// and trackers (`tr`) are sorted and deduplicated. Parameters are filtered by the `remove_query_parameters`
+/// and `keep_query_parameters` options, and sorted by the `sort_query_parameters` option.
+pub(crate) fn normalize_magnet_url(
+    url_string: &str,
+    options: &Options,
+) -> Result<String, NormalizeUrlError> {
+    // Skip `magnet:`
+    let query = url_string[7..].trim_start_matches('?');
+    let query = query.split_once('#').map_or(query, |(query, _)| query);
+
+    let mut parameters = vec![];
+    let mut trackers = vec![];
+    let mut trackers_index = None;
+    for (key, value) in form_urlencoded::parse(query.as_bytes()) {
+        let key = key.to_lowercase();
+        if !is_query_parameter_kept(&key, options)? {
+            continue;
+        }
+
+        match key.as_str() {
+            "tr" => {
+                trackers_index.get_or_insert(parameters.len());
+                trackers.push(value.into_owned());
+            }
+            "xt" => parameters.push((key, normalize_exact_topic(&value))),
+            _ => parameters.push((key, value.into_owned())),
+        }
+    }
+
+    trackers.sort();
+    trackers.dedup();
+    // Trackers take the place of the first one
+    if let Some(index) = trackers_index {
+        parameters.splice(
+            index..index,
+            trackers
+                .into_iter()
+                .map(|tracker| ("tr".to_string(), tracker)),
+        );
+    }
+
+    if options.sort_query_parameters {
+        parameters.sort_by(|a, b| a.0.cmp(&b.0));
+    }
+
+    Ok(format!(
+        "magnet:?{}",
+        parameters
+            .iter()
+            .map(|(key, value)| format!(
+                "{}={}",
+                utf8_percent_encode(key, MAGNET_ENCODE_SET),
+                utf8_percent_encode(value, MAGNET_ENCODE_SET)
+            ))
+            .collect::<Vec<_>>()
+            .join("&")
+    ))
+}
@@ -0,0 +1,118 @@
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use urlencoding::decode;
+
+use crate::{NormalizeUrlError, Options};
+
+/// Characters encoded in addresses and header fields: everything except `unreserved` and
+/// the `some-delims` of [RFC 6068](https://www.rfc-editor.org/rfc/rfc6068#section-2), minus `,`.
+const MAILTO_ENCODE_SET: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'_')
+    .remove(b'~')
+    .remove(b'!')
+    .remove(b'$')
+    .remove(b'\'')
+    .remove(b'(')
+    .remove(b')')
+    .remove(b'*')
+    .remove(b'+')
+    .remove(b';')
+    .remove(b':')
+    .remove(b'@');
+
+/// Characters encoded in the local part of an address: a decoded `@` would be read as the one before the domain.
+const LOCAL_PART_ENCODE_SET: &AsciiSet = &MAILTO_ENCODE_SET.add(b'@');
+
+/// Header fields whose values are lists of addresses.
+const ADDRESS_HEADERS: [&str; 3] = ["to", "cc", "bcc"];
+
+fn decode_component(component: &str) -> String {
+    decode(component)
+        .map(|decoded| decoded.into_owned())
+        .unwrap_or_else(|_| component.to_owned())
+}
+
+fn encode_component(component: &str) -> String {
+    utf8_percent_encode(component, MAILTO_ENCODE_SET).to_string()
+}
+
+fn normalize_address(address: &str, options: &Options) -> Result<String, NormalizeUrlError> {
+    let address = decode_component(address.trim());
+    let Some((local_part, domain)) = address.rsplit_once('@') else {
+        return Ok(encode_component(&address));
+    };
+
+    let domain = match domain.starts_with('[') {
+        true => domain.to_lowercase(),
+        false => options.idna_profile.map_host(domain)?,
+    };
+
+    Ok(format!(
+        "{}@{}",
+        utf8_percent_encode(local_part, LOCAL_PART_ENCODE_SET),
+        encode_component(&domain)
+    ))
+}
+
+fn normalize_addresses(addresses: &str, options: &Options) -> Result<String, NormalizeUrlError> {
+    let mut addresses = addresses
+        .split(',')
+        .filter(|address| !address.trim().is_empty())
+        .map(|address| normalize_address(address, options))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if options.sort_mailto_recipients {
+        addresses.sort();
+        addresses.dedup();
+    }
+
+    Ok(addresses.join(","))
+}
+
+/// Normalizes a `mailto:` URL as defined by [RFC 6068](https://www.rfc-editor.org/rfc/rfc6068).
+pub(crate) fn normalize_mailto_url(
+    url_string: &str,
+    options: &Options,
+) -> Result<String, NormalizeUrlError> {
+    // Skip `mailto:`
+    let url_string = &url_string[7..];
+    let (url_string, fragment) = match url_string.split_once('#') {
+        Some((url_string, fragment)) => (url_string, Some(fragment)),
+        None => (url_string, None),
+    };
+    let (to, query) = url_string.split_once('?').unwrap_or((url_string, ""));
+
+    let mut headers = vec![];
+    for header in query.split('&').filter(|header| !header.is_empty()) {
+        let (name, value) = header.split_once('=').unwrap_or((header, ""));
+        let name = decode_component(name).to_lowercase();
+        let value = match ADDRESS_HEADERS.contains(&name.as_str()) {
+            true => normalize_addresses(value, options)?,
+            false => encode_component(&decode_component(value)),
+        };
+        headers.push((encode_component(&name), value));
+    }
+
+    if options.sort_query_parameters {
+        headers.sort_by(|a, b| a.0.cmp(&b.0));
+    }
+
+    let mut result = format!("mailto:{}", normalize_addresses(to, options)?);
+    if !headers.is_empty() {
+        result += "?";
+        result += &headers
+            .iter()
+            .map(|(name, value)| format!("{}={}", name, value))
+            .collect::<Vec<_>>()
+            .join("&");
+    }
+
+    if let Some(fragment) = fragment.filter(|fragment| !options.strip_hash && !fragment.is_empty())
+    {
+        result += "#";
+        result += fragment;
+    }
+
+    Ok(result)
+}
@@ -0,0 +1,817 @@
+use std::collections::HashMap;
+
+use fancy_regex::{escape, Regex};
+use idna::AsciiDenyList;
+use url::Url;
+
+use crate::{clean_path, normalize_and_parse_url, scheme, IdnaProfile, NormalizeUrlError, Options};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TokenKind {
+    Open,
+    Close,
+    Regexp,
+    Name,
+    Char,
+    EscapedChar,
+    OtherModifier,
+    Asterisk,
+    End,
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    /// Byte index of the token in the pattern.
+    index: usize,
+    value: String,
+}
+
+fn invalid(pattern: &str, reason: &str) -> NormalizeUrlError {
+    NormalizeUrlError::InvalidUrlPattern(format!("{} in `{}`", reason, pattern))
+}
+
+fn is_name_start(c: char) -> bool {
+    c.is_alphabetic() || c == '_' || c == '$'
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '$'
+}
+
+fn tokenize(pattern: &str) -> Result<Vec<Token>, NormalizeUrlError> {
+    let chars = pattern.char_indices().collect::<Vec<_>>();
+    let mut tokens = vec![];
+    let mut i = 0;
+    while i < chars.len() {
+        let (index, c) = chars[i];
+        let (kind, value, length) = match c {
+            '*' => (TokenKind::Asterisk, c.to_string(), 1),
+            '+' | '?' => (TokenKind::OtherModifier, c.to_string(), 1),
+            '{' => (TokenKind::Open, c.to_string(), 1),
+            '}' => (TokenKind::Close, c.to_string(), 1),
+            '\\' => match chars.get(i + 1) {
+                Some((_, escaped)) => (TokenKind::EscapedChar, escaped.to_string(), 2),
+                None => return Err(invalid(pattern, "Trailing `\\`")),
+            },
+            ':' => {
+                let name = chars[i + 1..]
+                    .iter()
+                    .enumerate()
+                    .take_while(|(n, (_, c))| match n {
+                        0 => is_name_start(*c),
+                        _ => is_name_char(*c),
+                    })
+                    .map(|(_, (_, c))| *c)
+                    .collect::<String>();
+                match name.is_empty() {
+                    true => (TokenKind::Char, c.to_string(), 1),
+                    false => {
+                        let length = name.chars().count() + 1;
+                        (TokenKind::Name, name, length)
+                    }
+                }
+            }
+            '(' => {
+                let mut depth = 1;
+                let mut regexp = String::new();
+                let mut j = i + 1;
+                while depth > 0 {
+                    let Some((_, c)) = chars.get(j) else {
+                        return Err(invalid(pattern, "Unbalanced regexp group"));
+                    };
+                    match c {
+                        '\\' => {
+                            regexp.push('\\');
+                            j += 1;
+                            match chars.get(j) {
+                                Some((_, escaped)) => regexp.push(*escaped),
+                                None => return Err(invalid(pattern, "Trailing `\\`")),
+                            }
+                        }
+                        '(' => {
+                            depth += 1;
+                            regexp.push('(');
+                        }
+                        ')' => {
+                            depth -= 1;
+                            if depth > 0 {
+                                regexp.push(')');
+                            }
+                        }
+                        c => regexp.push(*c),
+                    }
+                    j += 1;
+                }
+                if regexp.is_empty() {
+                    return Err(invalid(pattern, "Empty regexp group"));
+                }
+                (TokenKind::Regexp, regexp, j - i)
+            }
+            c => (TokenKind::Char, c.to_string(), 1),
+        };
+        tokens.push(Token { kind, index, value });
+        i += length;
+    }
+    tokens.push(Token {
+        kind: TokenKind::End,
+        index: pattern.len(),
+        value: String::new(),
+    });
+    Ok(tokens)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Modifier {
+    None,
+    Optional,
+    ZeroOrMore,
+    OneOrMore,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum PartKind {
+    Fixed(String),
+    Regexp(String),
+    SegmentWildcard,
+    FullWildcard,
+}
+
+#[derive(Debug, Clone)]
+struct Part {
+    kind: PartKind,
+    name: String,
+    prefix: String,
+    suffix: String,
+    modifier: Modifier,
+}
+
+impl Part {
+    fn fixed(value: String, modifier: Modifier) -> Self {
+        Part {
+            kind: PartKind::Fixed(value),
+            name: String::new(),
+            prefix: String::new(),
+            suffix: String::new(),
+            modifier,
+        }
+    }
+}
+
+/// Parses a component pattern into parts. A `prefix` character right before a group becomes optional along with it,
+/// e.g. `/` in pathnames.
+fn parse_parts(pattern: &str, prefix: Option<char>) -> Result<Vec<Part>, NormalizeUrlError> {
+    let tokens = tokenize(pattern)?;
+    let mut parts = vec![];
+    let mut pending_fixed = String::new();
+    let mut next_index = 0;
+    let mut i = 0;
+
+    let take = |i: &mut usize, kinds: &[TokenKind]| -> Option<Token> {
+        match kinds.contains(&tokens[*i].kind) {
+            true => {
+                *i += 1;
+                Some(tokens[*i - 1].clone())
+            }
+            false => None,
+        }
+    };
+    let take_text = |i: &mut usize| {
+        let mut text = String::new();
+        while let Some(token) = take(i, &[TokenKind::Char, TokenKind::EscapedChar]) {
+            text += &token.value;
+        }
+        text
+    };
+    let take_modifier =
+        |i: &mut usize| match take(i, &[TokenKind::OtherModifier, TokenKind::Asterisk])
+            .as_ref()
+            .map(|token| token.value.as_str())
+        {
+            Some("?") => Modifier::Optional,
+            Some("*") => Modifier::ZeroOrMore,
+            Some("+") => Modifier::OneOrMore,
+            _ => Modifier::None,
+        };
+    let mut group_kind = |name: &Option<Token>, regexp: &Option<Token>| {
+        let kind = match regexp {
+            Some(token) if token.kind == TokenKind::Asterisk => PartKind::FullWildcard,
+            Some(token) => PartKind::Regexp(token.value.clone()),
+            None => PartKind::SegmentWildcard,
+        };
+        let name = match name {
+            Some(token) => token.value.clone(),
+            None => {
+                next_index += 1;
+                (next_index - 1).to_string()
+            }
+        };
+        (kind, name)
+    };
+
+    loop {
+        let char_token = take(&mut i, &[TokenKind::Char]);
+        let name = take(&mut i, &[TokenKind::Name]);
+        let regexp = match name {
+            Some(_) => take(&mut i, &[TokenKind::Regexp]),
+            None => take(&mut i, &[TokenKind::Regexp, TokenKind::Asterisk]),
+        };
+        if name.is_some() || regexp.is_some() {
+            let mut group_prefix = char_token.map(|token| token.value).unwrap_or_default();
+            if group_prefix.chars().next() != prefix || prefix.is_none() {
+                pending_fixed += &group_prefix;
+                group_prefix = String::new();
+            }
+            if !pending_fixed.is_empty() {
+                parts.push(Part::fixed(
+                    std::mem::take(&mut pending_fixed),
+                    Modifier::None,
+                ));
+            }
+            let (kind, name) = group_kind(&name, &regexp);
+            parts.push(Part {
+                kind,
+                name,
+                prefix: group_prefix,
+                suffix: String::new(),
+                modifier: take_modifier(&mut i),
+            });
+            continue;
+        }
+
+        if let Some(token) = char_token.or_else(|| take(&mut i, &[TokenKind::EscapedChar])) {
+            pending_fixed += &token.value;
+            continue;
+        }
+
+        if take(&mut i, &[TokenKind::Open]).is_some() {
+            let group_prefix = take_text(&mut i);
+            let name = take(&mut i, &[TokenKind::Name]);
+            let regexp = match name {
+                Some(_) => take(&mut i, &[TokenKind::Regexp]),
+                None => take(&mut i, &[TokenKind::Regexp, TokenKind::Asterisk]),
+            };
+            let suffix = take_text(&mut i);
+            if take(&mut i, &[TokenKind::Close]).is_none() {
+                return Err(invalid(pattern, "Unbalanced `{`"));
+            }
+            let modifier = take_modifier(&mut i);
+
+            if !pending_fixed.is_empty() {
+                parts.push(Part::fixed(
+                    std::mem::take(&mut pending_fixed),
+                    Modifier::None,
+                ));
+            }
+            if name.is_none() && regexp.is_none() {
+                parts.push(Part::fixed(group_prefix + &suffix, modifier));
+            } else {
+                let (kind, name) = group_kind(&name, &regexp);
+                parts.push(Part {
+                    kind,
+                    name,
+                    prefix: group_prefix,
+                    suffix,
+                    modifier,
+                });
+            }
+            continue;
+        }
+
+        if !pending_fixed.is_empty() {
+            parts.push(Part::fixed(
+                std::mem::take(&mut pending_fixed),
+                Modifier::None,
+            ));
+        }
+        if take(&mut i, &[TokenKind::End]).is_none() {
+            return Err(invalid(pattern, "Unexpected token"));
+        }
+        break;
+    }
+
+    Ok(parts)
+}
+
+/// Converts parts into a regex, returning it along with the names of its groups.
+fn parts_to_regex(
+    parts: &[Part],
+    delimiter: Option<char>,
+) -> Result<(Regex, Vec<String>), NormalizeUrlError> {
+    let segment_wildcard = match delimiter {
+        Some(delimiter) => format!("[^{}]+?", escape(&delimiter.to_string())),
+        None => ".+?".to_owned(),
+    };
+    let modifier_str = |modifier: Modifier| match modifier {
+        Modifier::None => "",
+        Modifier::Optional => "?",
+        Modifier::ZeroOrMore => "*",
+        Modifier::OneOrMore => "+",
+    };
+
+    let mut result = "^".to_owned();
+    let mut names = vec![];
+    for part in parts {
+        let value = match &part.kind {
+            PartKind::Fixed(value) => {
+                result += &match part.modifier {
+                    Modifier::None => escape(value).into_owned(),
+                    modifier => format!("(?:{}){}", escape(value), modifier_str(modifier)),
+                };
+                continue;
+            }
+            PartKind::Regexp(regexp) => regexp.clone(),
+            PartKind::SegmentWildcard => segment_wildcard.clone(),
+            PartKind::FullWildcard => ".*".to_owned(),
+        };
+
+        let group = format!("g{}", names.len());
+        names.push(part.name.clone());
+        let prefix = escape(&part.prefix);
+        let suffix = escape(&part.suffix);
+        result += &match (
+            part.prefix.is_empty() && part.suffix.is_empty(),
+            part.modifier,
+        ) {
+            (true, Modifier::None | Modifier::Optional) => {
+                format!("(?P<{}>{}){}", group, value, modifier_str(part.modifier))
+            }
+            (true, modifier) => {
+                format!("(?P<{}>(?:{}){})", group, value, modifier_str(modifier))
+            }
+            (false, Modifier::None | Modifier::Optional) => format!(
+                "(?:{}(?P<{}>{}){}){}",
+                prefix,
+                group,
+                value,
+                suffix,
+                modifier_str(part.modifier)
+            ),
+            (false, modifier) => format!(
+                "(?:{}(?P<{}>(?:{})(?:{}{}(?:{}))*){}){}",
+                prefix,
+                group,
+                value,
+                suffix,
+                prefix,
+                value,
+                suffix,
+                match modifier {
+                    Modifier::ZeroOrMore => "?",
+                    _ => "",
+                }
+            ),
+        };
+    }
+    result += "$";
+
+    let regex =
+        Regex::new(&result).map_err(|err| NormalizeUrlError::InvalidUrlPattern(err.to_string()))?;
+    Ok((regex, names))
+}
+
+#[derive(Debug, Clone)]
+struct Component {
+    regex: Regex,
+    names: Vec<String>,
+}
+
+impl Component {
+    fn exec(&self, input: &str) -> Option<UrlPatternComponentResult> {
+        let captures = self.regex.captures(input).ok()??;
+        let groups = self
+            .names
+            .iter()
+            .enumerate()
+            .map(|(index, name)| {
+                (
+                    name.clone(),
+                    captures
+                        .name(&format!("g{}", index))
+                        .map(|group| group.as_str().to_owned()),
+                )
+            })
+            .collect();
+        Some(UrlPatternComponentResult {
+            input: input.to_owned(),
+            groups,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+/// Patterns of the components of a URL, see [`UrlPattern::from_init`]. Omitted components match anything.
+pub struct UrlPatternInit {
+    pub protocol: Option<String>,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub hostname: Option<String>,
+    pub port: Option<String>,
+    pub pathname: Option<String>,
+    pub search: Option<String>,
+    pub hash: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// Match of a component, see [`UrlPattern::exec`].
+pub struct UrlPatternComponentResult {
+    /// The component of the normalized URL.
+    pub input: String,
+    /// Named groups (`:name`) by name and unnamed groups (`(regexp)` and `*`) by index. Optional groups which didn't match are `None`.
+    pub groups: HashMap<String, Option<String>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// Match of a URL, see [`UrlPattern::exec`].
+pub struct UrlPatternResult {
+    pub protocol: UrlPatternComponentResult,
+    pub username: UrlPatternComponentResult,
+    pub password: UrlPatternComponentResult,
+    pub hostname: UrlPatternComponentResult,
+    pub port: UrlPatternComponentResult,
+    pub pathname: UrlPatternComponentResult,
+    pub search: UrlPatternComponentResult,
+    pub hash: UrlPatternComponentResult,
+}
+
+#[derive(Debug, Clone)]
+/// A compiled [WHATWG URLPattern](https://urlpattern.spec.whatwg.org/) matching URLs normalized with [`normalize_url`](crate::normalize_url).
+///
+/// Patterns support fixed text, named groups (`:id`) with an optional regexp (`:id(\d+)`), regexp groups (`(\d+)`),
+/// wildcards (`*`), non-capturing groups (`{/}`) and the `?`, `*` and `+` modifiers. In pathnames, a `/` right before
+/// a group becomes optional along with it, so `/products/:id?` matches `/products`.
+///
+/// Fixed text of the pattern is normalized with the same options as the input, so `www.`, a trailing slash, a default port
+/// or a different case don't cause misses.
+pub struct UrlPattern {
+    components: [Component; 8],
+    options: Options,
+}
+
+/// Splits a pattern string into the patterns of its components.
+fn split_pattern(pattern: &str) -> Result<UrlPatternInit, NormalizeUrlError> {
+    let tokens = tokenize(pattern)?;
+    let is_char = |i: usize, value: &str| {
+        tokens
+            .get(i)
+            .is_some_and(|token| token.kind == TokenKind::Char && token.value == value)
+    };
+    let is_search_prefix = |i: usize| {
+        tokens[i].kind == TokenKind::OtherModifier
+            && tokens[i].value == "?"
+            && (i == 0
+                || !matches!(
+                    tokens[i - 1].kind,
+                    TokenKind::Name | TokenKind::Regexp | TokenKind::Close | TokenKind::Asterisk
+                ))
+    };
+    let slice =
+        |start: usize, end: usize| pattern[tokens[start].index..tokens[end].index].to_owned();
+
+    let mut depth = 0;
+    let Some(protocol_end) = (0..tokens.len()).find(|i| {
+        match tokens[*i].kind {
+            TokenKind::Open => depth += 1,
+            TokenKind::Close => depth -= 1,
+            _ => {}
+        }
+        depth == 0 && is_char(*i, ":") && is_char(*i + 1, "/") && is_char(*i + 2, "/")
+    }) else {
+        return Err(invalid(pattern, "Missing `://`"));
+    };
+
+    let authority_start = protocol_end + 3;
+    let mut boundaries = vec![];
+    let mut depth = 0;
+    for (i, token) in tokens.iter().enumerate().skip(authority_start) {
+        match token.kind {
+            TokenKind::Open => depth += 1,
+            TokenKind::Close => depth -= 1,
+            TokenKind::End => boundaries.push((i, ' ')),
+            _ if depth > 0 => {}
+            _ if is_char(i, "/") => boundaries.push((i, '/')),
+            _ if is_search_prefix(i) => boundaries.push((i, '?')),
+            _ if is_char(i, "#") => boundaries.push((i, '#')),
+            _ => {}
+        }
+    }
+
+    let mut init = UrlPatternInit {
+        protocol: Some(slice(0, protocol_end)),
+        port: Some(String::new()),
+        pathname: Some("/".to_owned()),
+        ..UrlPatternInit::default()
+    };
+
+    // Authority
+    let authority_end = boundaries[0].0;
+    let (host_start, userinfo) = match (authority_start..authority_end)
+        .rev()
+        .find(|i| is_char(*i, "@"))
+    {
+        Some(at) => (at + 1, Some((authority_start, at))),
+        None => (authority_start, None),
+    };
+    if let Some((start, end)) = userinfo {
+        match (start..end).find(|i| is_char(*i, ":")) {
+            Some(colon) => {
+                init.username = Some(slice(start, colon));
+                init.password = Some(slice(colon + 1, end));
+            }
+            None => init.username = Some(slice(start, end)),
+        }
+    }
+    let mut in_brackets = false;
+    let mut port_start = None;
+    for i in host_start..authority_end {
+        in_brackets = (in_brackets || is_char(i, "[")) && !is_char(i, "]");
+        if !in_brackets && is_char(i, ":") {
+            port_start = Some(i);
+        }
+    }
+    match port_start {
+        Some(colon) => {
+            init.hostname = Some(slice(host_start, colon));
+            init.port = Some(slice(colon + 1, authority_end));
+        }
+        None => init.hostname = Some(slice(host_start, authority_end)),
+    }
+
+    // Pathname, search and hash, each one ending where a later one starts
+    let mut kind = ' ';
+    let mut start = authority_end;
+    for (index, next_kind) in boundaries {
+        let may_end = match kind {
+            '/' => matches!(next_kind, '?' | '#' | ' '),
+            '?' => matches!(next_kind, '#' | ' '),
+            '#' => next_kind == ' ',
+            _ => true,
+        };
+        if !may_end {
+            continue;
+        }
+        match kind {
+            '/' => init.pathname = Some(slice(start, index)),
+            '?' => init.search = Some(slice(start + 1, index)),
+            '#' => init.hash = Some(slice(start + 1, index)),
+            _ => {}
+        }
+        kind = next_kind;
+        start = index;
+    }
+
+    Ok(init)
+}
+
+/// Maps the labels of fixed hostname text like the host of an input URL, e.g. `München` → `xn--mnchen-3ya`.
+///
+/// Labels which can't be mapped are only lowercased, they can't match any input anyway.
+fn map_hostname(value: &str, options: &Options) -> String {
+    value
+        .split('.')
+        .map(|label| {
+            let ascii = match label.is_empty() {
+                true => return String::new(),
+                false => idna::domain_to_ascii_cow(label.as_bytes(), AsciiDenyList::URL),
+            };
+            match ascii {
+                Ok(ascii) if options.idna_profile != IdnaProfile::default() => {
+                    options.idna_profile.map_host(&ascii).ok()
+                }
+                Ok(ascii) => Some(ascii.into_owned()),
+                Err(_) => None,
+            }
+            .unwrap_or_else(|| label.to_lowercase())
+        })
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+/// Cleans fixed pathname text like the path of an input URL: duplicate slashes are removed, URI octets decoded,
+/// and characters which aren't allowed in a path encoded, e.g. `/a//%7Eb c` → `/a/~b%20c`.
+fn map_pathname(value: &str) -> Result<String, NormalizeUrlError> {
+    let path = clean_path(value)?;
+    let mut url_obj = Url::parse("http://host").unwrap();
+    url_obj.set_path(&path);
+    Ok(match path.starts_with('/') {
+        true => url_obj.path().to_owned(),
+        false => url_obj.path()[1..].to_owned(),
+    })
+}
+
+/// Filters and sorts fixed search text like the query of an input URL, e.g. `b=2&a=1&utm_source=x` → `a=1&b=2`.
+fn map_search(value: &str, options: &Options) -> Result<String, NormalizeUrlError> {
+    let (_, url_obj) = normalize_and_parse_url(&format!("http://host/?{}", value), options)?;
+    Ok(url_obj.unwrap().query().unwrap_or_default().to_owned())
+}
+
+impl UrlPattern {
+    /// Compiles a pattern string, e.g. `https://*.example.com/products/:id{/}?`.
+    ///
+    /// Omitted username, password, search and hash match anything, an omitted port matches the default port,
+    /// and an omitted pathname matches `/`. Relative patterns aren't supported.
+    ///
+    /// Fixed text is normalized like the input, so a URL without wildcards matches itself: hostnames are mapped to
+    /// ASCII, pathnames are cleaned and decoded, and a search without wildcards is filtered and sorted.
+    ///
+    /// ```
+    /// use normalize_url_rs::{OptionsBuilder, UrlPattern};
+    ///
+    /// let options = OptionsBuilder::default().build().unwrap();
+    /// let pattern = UrlPattern::new("https://*.example.com/products/:id{/}?", &options).unwrap();
+    /// let result = pattern.exec("HTTPS://shop.Example.com:443/products/42/").unwrap().unwrap();
+    ///
+    /// assert_eq!(result.hostname.groups["0"].as_deref(), Some("shop"));
+    /// assert_eq!(result.pathname.groups["id"].as_deref(), Some("42"));
+    /// ```
+    pub fn new(pattern: &str, options: &Options) -> Result<Self, NormalizeUrlError> {
+        Self::from_init(&split_pattern(pattern.trim())?, options)
+    }
+
+    /// Compiles the patterns of the components of a URL.
+    pub fn from_init(init: &UrlPatternInit, options: &Options) -> Result<Self, NormalizeUrlError> {
+        let pattern_of = |component: &Option<String>| component.clone().unwrap_or("*".to_owned());
+        let scheme_mapping = scheme::resolve_scheme_mapping(options)?;
+
+        let mut protocol = parse_parts(&pattern_of(&init.protocol), None)?;
+        for part in &mut protocol {
+            if let PartKind::Fixed(value) = &mut part.kind {
+                *value = value.to_lowercase();
+            }
+        }
+        if let [Part {
+            kind: PartKind::Fixed(value),
+            modifier: Modifier::None,
+            ..
+        }] = protocol.as_mut_slice()
+        {
+            if let Some(mapped) = scheme_mapping.get(value.as_str()) {
+                *value = mapped.clone();
+            }
+        }
+
+        let mut hostname = parse_parts(&pattern_of(&init.hostname), None)?;
+        for part in &mut hostname {
+            if let PartKind::Fixed(value) = &mut part.kind {
+                *value = map_hostname(value, options);
+            }
+        }
+        if let Some(Part {
+            kind: PartKind::Fixed(value),
+            modifier: Modifier::None,
+            ..
+        }) = hostname.first_mut()
+        {
+            if options.strip_www && value.starts_with("www.") && value.matches('.').count() > 1 {
+                value.replace_range(..4, "");
+            }
+        }
+        if let Some(Part {
+            kind: PartKind::Fixed(value),
+            modifier: Modifier::None,
+            ..
+        }) = hostname.last_mut()
+        {
+            if value.ends_with('.') {
+                value.pop();
+            }
+        }
+
+        let mut port = parse_parts(&pattern_of(&init.port), None)?;
+        if let (
+            [Part {
+                kind: PartKind::Fixed(value),
+                modifier: Modifier::None,
+                ..
+            }],
+            [Part {
+                kind: PartKind::Fixed(scheme),
+                modifier: Modifier::None,
+                ..
+            }],
+        ) = (port.as_slice(), protocol.as_slice())
+        {
+            if options
+                .default_ports
+                .get(scheme)
+                .map(u16::to_string)
+                .as_ref()
+                == Some(value)
+                || Url::parse(&format!("{}://host", scheme)).is_ok_and(|url_obj| {
+                    url_obj
+                        .port_or_known_default()
+                        .map(|port| port.to_string())
+                        .as_ref()
+                        == Some(value)
+                })
+            {
+                port.clear();
+            }
+        }
+
+        let mut pathname = parse_parts(&pattern_of(&init.pathname), Some('/'))?;
+        for index in 0..pathname.len() {
+            // The slash before a group is its prefix, e.g. in `/a//:id`
+            let is_before_slash = pathname
+                .get(index + 1)
+                .is_some_and(|next| next.prefix.starts_with('/'));
+            if let PartKind::Fixed(value) = &mut pathname[index].kind {
+                *value = map_pathname(value)?;
+                if is_before_slash && value.ends_with('/') {
+                    value.pop();
+                }
+            }
+        }
+        // The root path keeps its slash
+        let is_root = pathname.len() == 1 && pathname[0].kind == PartKind::Fixed("/".to_owned());
+        if let Some(Part {
+            kind: PartKind::Fixed(value),
+            modifier: Modifier::None,
+            ..
+        }) = pathname.last_mut()
+        {
+            if options.remove_trailing_slash && !is_root && value.ends_with('/') {
+                value.pop();
+            }
+        }
+
+        // Only a search without wildcards can be filtered and sorted
+        let mut search = parse_parts(&pattern_of(&init.search), None)?;
+        if let [Part {
+            kind: PartKind::Fixed(value),
+            modifier: Modifier::None,
+            ..
+        }] = search.as_mut_slice()
+        {
+            *value = map_search(value, options)?;
+        }
+
+        let compile = |parts: &[Part], delimiter: Option<char>| {
+            parts_to_regex(parts, delimiter).map(|(regex, names)| Component { regex, names })
+        };
+        Ok(UrlPattern {
+            components: [
+                compile(&protocol, None)?,
+                compile(&parse_parts(&pattern_of(&init.username), None)?, None)?,
+                compile(&parse_parts(&pattern_of(&init.password), None)?, None)?,
+                compile(&hostname, Some('.'))?,
+                compile(&port, None)?,
+                compile(&pathname, Some('/'))?,
+                compile(&search, None)?,
+                compile(&parse_parts(&pattern_of(&init.hash), None)?, None)?,
+            ],
+            options: options.clone(),
+        })
+    }
+
+    /// Normalizes `url` with [`normalize_url`](crate::normalize_url) and matches it against the pattern.
+    ///
+    /// Returns `None` if it doesn't match.
+    pub fn exec(&self, url: &str) -> Result<Option<UrlPatternResult>, NormalizeUrlError> {
+        // Patterns are absolute, so they never match relative references
+        let Some(url_obj) = normalize_and_parse_url(url, &self.options)?.1 else {
+            return Ok(None);
+        };
+
+        let port = url_obj
+            .port()
+            .map(|port| port.to_string())
+            .unwrap_or_default();
+        let inputs = [
+            url_obj.scheme(),
+            url_obj.username(),
+            url_obj.password().unwrap_or_default(),
+            url_obj.host_str().unwrap_or_default(),
+            &port,
+            url_obj.path(),
+            url_obj.query().unwrap_or_default(),
+            url_obj.fragment().unwrap_or_default(),
+        ];
+
+        let mut results = vec![];
+        for (component, input) in self.components.iter().zip(inputs) {
+            match component.exec(input) {
+                Some(result) => results.push(result),
+                None => return Ok(None),
+            }
+        }
+
+        let mut results = results.into_iter();
+        let mut next = || results.next().unwrap();
+        Ok(Some(UrlPatternResult {
+            protocol: next(),
+            username: next(),
+            password: next(),
+            hostname: next(),
+            port: next(),
+            pathname: next(),
+            search: next(),
+            hash: next(),
+        }))
+    }
+
+    /// Returns `true` if `url`, normalized with [`normalize_url`](crate::normalize_url), matches the pattern.
+    pub fn test(&self, url: &str) -> Result<bool, NormalizeUrlError> {
+        Ok(self.exec(url)?.is_some())
+    }
+}
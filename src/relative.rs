@@ -0,0 +1,205 @@
+use fancy_regex::Regex;
+use lazy_static::lazy_static;
+use url::{form_urlencoded, Position};
+
+use crate::file::{dot_segment, encode_segment};
+use crate::{
+    is_query_parameter_kept, normalize_and_parse_url, normalize_url_with_base, parse_url,
+    NormalizeUrlError, Options,
+};
+
+/// Normalizes a relative reference without a base, e.g. `./foo//bar/../baz?b=2&a=1` → `./foo/baz?a=1&b=2`.
+///
+/// Dot segments are resolved as far as possible: leading `..` segments of a path-relative reference are kept,
+/// while those of an absolute-path reference are removed. Path-relative references always start with `./` or `../`,
+/// so the result is still recognized as a relative reference.
+///
+/// Directory indices and trailing slashes are removed as for absolute URLs, except the trailing slash of a path ending
+/// with `..`, e.g. `../`, which would otherwise no longer be recognized as a relative reference.
+pub(crate) fn normalize_relative_reference(
+    url_string: &str,
+    options: &Options,
+) -> Result<String, NormalizeUrlError> {
+    lazy_static! {
+        static ref DUPLICATE_SLASHES_RE: Regex = Regex::new(r"\/{2,}").unwrap();
+        static ref TEXT_FRAGMENT_RE: Regex = Regex::new(r"#?:~:text.*?$").unwrap();
+    }
+
+    let (url_string, fragment) = match url_string.split_once('#') {
+        Some((url_string, fragment)) => (url_string, Some(fragment)),
+        None => (url_string, None),
+    };
+    let (path, query) = url_string.split_once('?').unwrap_or((url_string, ""));
+
+    let path = DUPLICATE_SLASHES_RE.replace_all(path, "/");
+    let is_absolute = path.starts_with('/');
+
+    let mut resolved: Vec<String> = vec![];
+    let mut is_directory = false;
+    for segment in path.split('/').skip(is_absolute as usize) {
+        let segment = dot_segment(segment, true);
+        is_directory = matches!(segment, "" | "." | "..");
+        match segment {
+            "" | "." => {}
+            ".." => match resolved.last().map(String::as_str) {
+                Some("..") | None if !is_absolute => resolved.push("..".to_owned()),
+                Some(_) => {
+                    resolved.pop();
+                }
+                None => {}
+            },
+            segment => resolved.push(encode_segment(segment, true)),
+        }
+    }
+
+    // Remove directory index
+    if let Some(last) = resolved.last().filter(|last| *last != "..") {
+        for regex in options.remove_directory_index.regexs() {
+            if regex
+                .is_match(last)
+                .map_err(Into::into)
+                .map_err(NormalizeUrlError::UnexpectedError)?
+            {
+                resolved.pop();
+                is_directory = true;
+                break;
+            }
+        }
+    }
+
+    let mut result = match (is_absolute, resolved.first().map(String::as_str)) {
+        (true, _) => "/".to_owned(),
+        (false, Some("..")) => String::new(),
+        (false, _) => "./".to_owned(),
+    };
+    result += &resolved.join("/");
+    let keeps_trailing_slash =
+        !options.remove_trailing_slash || resolved.last().is_some_and(|last| last == "..");
+    if is_directory && keeps_trailing_slash && !resolved.is_empty() {
+        result.push('/');
+    }
+
+    let mut query_pairs = vec![];
+    for (key, value) in form_urlencoded::parse(query.as_bytes()) {
+        if is_query_parameter_kept(&key, options)? {
+            query_pairs.push((key, value));
+        }
+    }
+    if options.sort_query_parameters {
+        query_pairs.sort_by(|a, b| a.0.cmp(&b.0));
+    }
+    if !query_pairs.is_empty() {
+        result += "?";
+        result += &form_urlencoded::Serializer::new(String::new())
+            .extend_pairs(query_pairs)
+            .finish();
+    }
+
+    let fragment = match (fragment, options.strip_hash) {
+        (Some(fragment), false) if options.strip_text_fragment => {
+            Some(TEXT_FRAGMENT_RE.replace(fragment, "").into_owned())
+        }
+        (Some(fragment), false) => Some(fragment.to_owned()),
+        _ => None,
+    };
+    if let Some(fragment) = fragment.filter(|fragment| !fragment.is_empty()) {
+        result += "#";
+        result += &fragment;
+    }
+
+    Ok(result)
+}
+
+/// Returns the path of `path` relative to the directory of `base_path`, e.g. `/a/c/d` against `/a/b/x` → `../c/d`.
+fn relative_path(path: &str, base_path: &str) -> String {
+    let segments = path.split('/').skip(1).collect::<Vec<_>>();
+    let base_directory = base_path.split('/').skip(1).collect::<Vec<_>>();
+    let base_directory = &base_directory[..base_directory.len().saturating_sub(1)];
+
+    let (directory, file) = segments.split_at(segments.len().saturating_sub(1));
+    let common = directory
+        .iter()
+        .zip(base_directory)
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let mut result = "../".repeat(base_directory.len() - common);
+    result += &[&directory[common..], file].concat().join("/");
+    // A first segment containing `:` would be read as a scheme
+    if result.is_empty()
+        || result.starts_with('/')
+        || result.split('/').next().unwrap().contains(':')
+    {
+        result.insert_str(0, "./");
+    }
+    result
+}
+
+/// Computes the shortest relative reference that resolves against `base` to the normalized `url`, e.g. `../x?y`,
+/// `//other-host/x`, `?q` or `#frag`.
+///
+/// `url` is normalized with [`normalize_url_with_base`] first, and every candidate is checked to round trip:
+/// `normalize_url_with_base(&make_relative(url, base, &options)?, base, &options)` is always the normalized `url`.
+/// The normalized `url` itself is returned when no relative reference round trips, e.g. for a different scheme.
+///
+/// ```
+/// use normalize_url_rs::{make_relative, OptionsBuilder};
+///
+/// let options = OptionsBuilder::default().build().unwrap();
+/// let result = make_relative("https://example.com/docs/api/?b=2&a=1", "https://example.com/docs/guide/intro", &options);
+///
+/// assert_eq!(result.unwrap(), "../api?a=1&b=2")
+/// ```
+pub fn make_relative(
+    url: &str,
+    base: &str,
+    options: &Options,
+) -> Result<String, NormalizeUrlError> {
+    let normalized = normalize_url_with_base(url, base, options)?;
+    let (Ok(url_obj), Ok(base_obj)) = (parse_url(&normalized, options), parse_url(base, options))
+    else {
+        return Ok(normalized);
+    };
+    if url_obj.cannot_be_a_base() || base_obj.cannot_be_a_base() {
+        return Ok(normalized);
+    }
+    // Schemes and authorities are compared once normalized, e.g. a `www.` base matches, while paths are resolved
+    // against the base as given
+    let normalized_base_obj = match normalize_and_parse_url(base, options) {
+        Ok((_, Some(normalized_base_obj))) => normalized_base_obj,
+        _ => base_obj.clone(),
+    };
+
+    let suffix = &url_obj[Position::AfterPath..];
+    let mut candidates = vec![];
+    if url_obj.scheme() == normalized_base_obj.scheme() {
+        candidates.push(format!("//{}", &url_obj[Position::BeforeUsername..]));
+        if url_obj[..Position::AfterPort] == normalized_base_obj[..Position::AfterPort] {
+            candidates.push(format!("{}{}", url_obj.path(), suffix));
+            candidates.push(format!(
+                "{}{}",
+                relative_path(url_obj.path(), base_obj.path()),
+                suffix
+            ));
+            if url_obj.path() == base_obj.path() {
+                candidates.push(url_obj[Position::AfterPath..].to_owned());
+                if url_obj.query() == base_obj.query() {
+                    candidates.push(url_obj[Position::AfterQuery..].to_owned());
+                }
+            }
+        }
+    }
+
+    // Shortest first, and the most specific one among equally short candidates
+    candidates.reverse();
+    candidates.sort_by_key(String::len);
+    for candidate in candidates {
+        if normalize_url_with_base(&candidate, base, options)
+            .is_ok_and(|result| result == normalized)
+        {
+            return Ok(candidate);
+        }
+    }
+
+    Ok(normalized)
+}
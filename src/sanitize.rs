@@ -0,0 +1,211 @@
+use derive_builder::Builder;
+use fancy_regex::{Captures, Regex};
+use lazy_static::lazy_static;
+use percent_encoding::{utf8_percent_encode, AsciiSet, CONTROLS};
+
+use crate::{normalize_url, NormalizeUrlError, Options};
+
+/// Characters percent-encoded in safe links, so they can't end an HTML attribute value.
+const ATTRIBUTE_ENCODE_SET: &AsciiSet = &CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'\'')
+    .add(b'<')
+    .add(b'>')
+    .add(b'`');
+
+/// Schemes able to run script, always unsafe.
+const SCRIPT_SCHEMES: [&str; 3] = ["javascript", "vbscript", "livescript"];
+
+#[derive(Builder, Debug, Clone)]
+#[builder(setter(into))]
+/// Link sanitization options, see [`sanitize_url`].
+pub struct SanitizeOptions {
+    /// Schemes of safe links. Scheme-less links like `example.com` are checked as `default_protocol` links,
+    /// and relative references like `/path` are always safe.
+    ///
+    /// `javascript:`, `vbscript:` and `livescript:` are unsafe even when allowed, and so are `data:` URLs
+    /// of any media type other than `image/png`, `image/gif`, `image/jpeg` and `image/webp`.
+    ///
+    /// Default value: `http`, `https`, `mailto`, `ftp`, `tel`.
+    #[builder(
+        default = "vec![\"http\".to_string(), \"https\".to_string(), \"mailto\".to_string(), \"ftp\".to_string(), \"tel\".to_string()]"
+    )]
+    pub allowed_schemes: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// Verdict of [`sanitize_url`].
+pub enum LinkVerdict {
+    /// The link is safe. Holds the normalized URL, in which characters that could end an HTML attribute value
+    /// (`"`, `'`, `<`, `>`, `` ` ``, whitespace and control characters) are percent-encoded.
+    Safe(String),
+    /// The link can run script or render active content. Holds its lowercased scheme.
+    Unsafe(String),
+    /// The scheme of the link isn't in `allowed_schemes`. Holds its lowercased scheme.
+    UnknownScheme(String),
+    /// The link isn't a valid URL.
+    Invalid,
+}
+
+/// Decodes the HTML character references used to obfuscate schemes, e.g. `&#106;avascript&colon;`.
+fn decode_character_references(input: &str) -> String {
+    lazy_static! {
+        static ref RE: Regex = Regex::new(
+            r"(?i)&(?:#x([\da-f]+)|#(\d+)|(colon|tab|newline|sol|lpar|rpar|period|amp));?"
+        )
+        .unwrap();
+    }
+
+    RE.replace_all(input, |captures: &Captures| {
+        let code_point = match (captures.get(1), captures.get(2), captures.get(3)) {
+            (Some(hex), _, _) => u32::from_str_radix(hex.as_str(), 16).ok(),
+            (_, Some(decimal), _) => decimal.as_str().parse().ok(),
+            (_, _, Some(name)) => match name.as_str().to_lowercase().as_str() {
+                "colon" => Some(':' as u32),
+                "tab" => Some('\t' as u32),
+                "newline" => Some('\n' as u32),
+                "sol" => Some('/' as u32),
+                "lpar" => Some('(' as u32),
+                "rpar" => Some(')' as u32),
+                "period" => Some('.' as u32),
+                _ => Some('&' as u32),
+            },
+            _ => None,
+        };
+        code_point
+            .and_then(char::from_u32)
+            .unwrap_or(char::REPLACEMENT_CHARACTER)
+            .to_string()
+    })
+    .into_owned()
+}
+
+/// Returns `true` for `data:` URLs which can't render active content.
+fn is_safe_data_url(url_string: &str) -> bool {
+    lazy_static! {
+        static ref RE: Regex = Regex::new(r"(?i)^data:image\/(?:png|gif|jpeg|webp)[;,]").unwrap();
+    }
+    RE.is_match(url_string).unwrap_or(false)
+}
+
+/// Classifies a user-submitted link as safe, unsafe, or of an unknown scheme, and normalizes safe links with [`normalize_url`].
+///
+/// The link is read the way a browser reads an `href` attribute: HTML character references are decoded,
+/// tabs and newlines are removed, and control characters can't hide the scheme, so `java\tscript:`,
+/// `&#106;avascript:` and `JaVaScRiPt:` are all detected. `\` is read as `/`, so network-path references like `/\\example.com`
+/// are checked as `default_protocol` links, like `//example.com`.
+///
+/// Links which can't be parsed get the [`LinkVerdict::Invalid`] verdict: errors are only returned for invalid `options`.
+///
+/// ```
+/// use normalize_url_rs::{sanitize_url, LinkVerdict, OptionsBuilder, SanitizeOptionsBuilder};
+///
+/// let options = OptionsBuilder::default().build().unwrap();
+/// let sanitize_options = SanitizeOptionsBuilder::default().build().unwrap();
+///
+/// let verdict = sanitize_url(" java\tscript:alert(1)", &sanitize_options, &options);
+/// assert_eq!(verdict.unwrap(), LinkVerdict::Unsafe("javascript".to_string()));
+///
+/// let verdict = sanitize_url("https://www.example.com/", &sanitize_options, &options);
+/// assert_eq!(verdict.unwrap(), LinkVerdict::Safe("https://example.com".to_string()));
+/// ```
+pub fn sanitize_url(
+    url: &str,
+    sanitize_options: &SanitizeOptions,
+    options: &Options,
+) -> Result<LinkVerdict, NormalizeUrlError> {
+    lazy_static! {
+        static ref SCHEME_RE: Regex = Regex::new(r"^([^:\/\\?#]*):").unwrap();
+        static ref VALID_SCHEME_RE: Regex = Regex::new(r"^[a-z][a-z\d+.-]*$").unwrap();
+        static ref NETWORK_PATH_RE: Regex = Regex::new(r"^[\/\\]{2}").unwrap();
+        static ref RELATIVE_RE: Regex = Regex::new(r"^(?:[\/\\]|\.|\?|#|$)").unwrap();
+        static ref HIERARCHICAL_RE: Regex = Regex::new(r"^[a-z][a-z\d+.-]*:\/\/").unwrap();
+    }
+
+    // The decoded link is only used for classification, safe links are re-encoded
+    let url_string = decode_character_references(url.trim())
+        .replace(['\t', '\n', '\r'], "")
+        .trim_matches(|c: char| c <= ' ')
+        .to_owned();
+    let safe = |url_string: &str| {
+        Ok(LinkVerdict::Safe(
+            utf8_percent_encode(url_string, ATTRIBUTE_ENCODE_SET).to_string(),
+        ))
+    };
+    let normalize = |url_string: &str| match normalize_url(url_string, options) {
+        Ok(url_string) => safe(&url_string),
+        Err(
+            err @ (NormalizeUrlError::ForceHttpAndHttpAreExclusive
+            | NormalizeUrlError::ConflictingSchemeMapping(_)),
+        ) => Err(err),
+        Err(_) => Ok(LinkVerdict::Invalid),
+    };
+    let is_allowed = |scheme: &str| {
+        sanitize_options
+            .allowed_schemes
+            .iter()
+            .any(|allowed| allowed.eq_ignore_ascii_case(scheme))
+    };
+
+    let scheme = SCHEME_RE
+        .captures(&url_string)
+        .map_err(Into::into)
+        .map_err(NormalizeUrlError::UnexpectedError)?
+        .map(|captures| {
+            // Control characters and spaces are ignored by some user agents
+            let scheme = captures[1]
+                .chars()
+                .filter(|c| *c > ' ')
+                .collect::<String>()
+                .to_lowercase();
+            (scheme, captures[0].len())
+        })
+        .filter(|(scheme, _)| VALID_SCHEME_RE.is_match(scheme).unwrap_or(false));
+
+    let with_scheme = |scheme: &str, rest: &str| match NETWORK_PATH_RE.is_match(rest) {
+        Ok(true) => format!("{}://{}", scheme, &rest[2..]),
+        _ => format!("{}:{}", scheme, rest),
+    };
+    let (scheme, url_string) = match scheme {
+        Some((scheme, scheme_len)) => {
+            let url_string = with_scheme(&scheme, &url_string[scheme_len..]);
+            (scheme, url_string)
+        }
+        // Network-path references, e.g. `//example.com` or `/\example.com`, lead to another host
+        None if NETWORK_PATH_RE.is_match(&url_string).unwrap_or(false) => {
+            let scheme = options.default_protocol.to_lowercase();
+            let url_string = with_scheme(&scheme, &url_string);
+            (scheme, url_string)
+        }
+        // Path-relative, query-relative and fragment-only references stay on the current page
+        None if RELATIVE_RE.is_match(&url_string).unwrap_or(false) => return safe(&url_string),
+        // Other scheme-less links, e.g. `example.com/path`, get the default protocol
+        None => {
+            return match is_allowed(&options.default_protocol) {
+                true => normalize(&url_string),
+                false => Ok(LinkVerdict::UnknownScheme(
+                    options.default_protocol.to_lowercase(),
+                )),
+            };
+        }
+    };
+
+    if SCRIPT_SCHEMES.contains(&scheme.as_str())
+        || (scheme == "data" && !is_safe_data_url(&url_string))
+    {
+        return Ok(LinkVerdict::Unsafe(scheme));
+    }
+
+    if !is_allowed(&scheme) {
+        return Ok(LinkVerdict::UnknownScheme(scheme));
+    }
+
+    // Only hierarchical URLs and the schemes with a dedicated normalizer can be normalized
+    let is_normalizable = HIERARCHICAL_RE.is_match(&url_string).unwrap_or(false)
+        || matches!(scheme.as_str(), "mailto" | "file" | "magnet");
+    match is_normalizable {
+        true => normalize(&url_string),
+        false => safe(&url_string),
+    }
+}
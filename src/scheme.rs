@@ -0,0 +1,77 @@
+use std::collections::{BTreeMap, HashMap};
+use url::{Position, Url};
+
+use crate::{NormalizeUrlError, Options};
+
+/// Combines the `scheme_mapping`, `force_http` and `force_https` options into a single mapping.
+pub(crate) fn resolve_scheme_mapping(
+    options: &Options,
+) -> Result<BTreeMap<String, String>, NormalizeUrlError> {
+    resolve_scheme_rules(
+        &options.scheme_mapping,
+        options.force_http,
+        options.force_https,
+    )
+}
+
+/// Combines `scheme_mapping` rules with the `force_http` and `force_https` shorthands, rejecting conflicting rules.
+pub(crate) fn resolve_scheme_rules(
+    scheme_mapping: &HashMap<String, String>,
+    force_http: bool,
+    force_https: bool,
+) -> Result<BTreeMap<String, String>, NormalizeUrlError> {
+    if force_http && force_https {
+        return Err(NormalizeUrlError::ForceHttpAndHttpAreExclusive);
+    }
+
+    let mut mapping = BTreeMap::new();
+    let shorthands = [
+        (force_http, "https", "http"),
+        (force_https, "http", "https"),
+    ];
+    let rules = scheme_mapping
+        .iter()
+        .map(|(from, to)| (from.as_str(), to.as_str()))
+        .chain(
+            shorthands
+                .into_iter()
+                .filter(|(enabled, _, _)| *enabled)
+                .map(|(_, from, to)| (from, to)),
+        );
+
+    for (from, to) in rules {
+        let from = from.to_ascii_lowercase();
+        let to = to.to_ascii_lowercase();
+        if from == to {
+            continue;
+        }
+
+        match mapping.get(&from) {
+            Some(existing) if *existing != to => {
+                return Err(NormalizeUrlError::ConflictingSchemeMapping(from));
+            }
+            _ => {
+                mapping.insert(from, to);
+            }
+        }
+    }
+
+    // Chained rules (`ws → wss`, `wss → ws`) would make the result depend on the order they are applied in
+    if let Some(to) = mapping.values().find(|to| mapping.contains_key(*to)) {
+        return Err(NormalizeUrlError::ConflictingSchemeMapping(to.clone()));
+    }
+
+    Ok(mapping)
+}
+
+/// Replaces the scheme of `url`, including changes between special and non-special schemes,
+/// which `Url::set_scheme` refuses.
+pub(crate) fn replace_scheme(url: &mut Url, scheme: &str) -> Result<(), NormalizeUrlError> {
+    if url.set_scheme(scheme).is_err() {
+        *url = Url::parse(&format!("{}{}", scheme, &url[Position::AfterScheme..]))
+            .map_err(Into::into)
+            .map_err(NormalizeUrlError::UnexpectedError)?;
+    }
+
+    Ok(())
+}
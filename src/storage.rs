@@ -0,0 +1,213 @@
+use fancy_regex::Regex;
+use lazy_static::lazy_static;
+use percent_encoding::{percent_decode_str, percent_encode, AsciiSet, CONTROLS};
+use url::Url;
+
+use crate::{is_query_parameter_kept, NormalizeUrlError, Options};
+
+/// Characters encoded in object keys.
+const KEY_ENCODE_SET: &AsciiSet = &CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'#')
+    .add(b'%')
+    .add(b'<')
+    .add(b'>')
+    .add(b'?')
+    .add(b'`')
+    .add(b'{')
+    .add(b'}');
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Controls whether object-storage URLs (Amazon S3, Google Cloud Storage, Azure Blob Storage) will be canonicalized.
+pub enum ObjectStorageOptions {
+    /// Object-storage URLs will be normalized like any other URL.
+    None,
+    /// Object-storage URLs will be rewritten to `s3://bucket/key`, `gs://bucket/key` and `az://account/container/blob`.
+    Native,
+    /// Object-storage URLs will be rewritten to `https://bucket.s3.amazonaws.com/key`, `https://storage.googleapis.com/bucket/key`
+    /// and `https://account.blob.core.windows.net/container/blob`.
+    Https,
+}
+
+enum Provider {
+    S3,
+    Gcs,
+    Azure(String),
+}
+
+struct StorageObject {
+    provider: Provider,
+    bucket: String,
+    key: String,
+}
+
+/// Splits `path` into its first segment and the rest.
+fn split_bucket(path: &str) -> Option<(String, String)> {
+    let path = path.strip_prefix('/').unwrap_or(path);
+    let (bucket, key) = path.split_once('/').unwrap_or((path, ""));
+    match bucket.is_empty() {
+        true => None,
+        false => Some((bucket.to_owned(), key.to_owned())),
+    }
+}
+
+fn parse_storage_object(url_obj: &Url) -> Option<StorageObject> {
+    lazy_static! {
+        static ref S3_VIRTUAL_HOSTED_RE: Regex = Regex::new(
+            r"^(.+)\.s3(?:-website)?(?:[.-](?:dualstack\.)?[a-z\d-]+)?\.amazonaws\.com(?:\.cn)?$"
+        )
+        .unwrap();
+        static ref S3_PATH_STYLE_RE: Regex =
+            Regex::new(r"^s3(?:[.-](?:dualstack\.)?[a-z\d-]+)?\.amazonaws\.com(?:\.cn)?$").unwrap();
+        static ref GCS_VIRTUAL_HOSTED_RE: Regex =
+            Regex::new(r"^(.+)\.storage\.googleapis\.com$").unwrap();
+        static ref AZURE_RE: Regex =
+            Regex::new(r"^([a-z\d]+)\.(?:blob|dfs)\.core\.windows\.net$").unwrap();
+    }
+
+    let host = url_obj.host_str()?.trim_end_matches('.').to_lowercase();
+    let path = url_obj.path();
+    let (provider, (bucket, key)) = match url_obj.scheme() {
+        "s3" | "s3a" | "s3n" => (
+            Provider::S3,
+            (host, path.trim_start_matches('/').to_owned()),
+        ),
+        "gs" => (
+            Provider::Gcs,
+            (host, path.trim_start_matches('/').to_owned()),
+        ),
+        "az" => (Provider::Azure(host), split_bucket(path)?),
+        "wasb" | "wasbs" | "abfs" | "abfss" => {
+            let account = AZURE_RE.captures(&host).ok()??[1].to_owned();
+            let container = url_obj.username();
+            match container.is_empty() {
+                true => (Provider::Azure(account), split_bucket(path)?),
+                false => (
+                    Provider::Azure(account),
+                    (
+                        container.to_owned(),
+                        path.trim_start_matches('/').to_owned(),
+                    ),
+                ),
+            }
+        }
+        "http" | "https" => {
+            if let Ok(Some(captures)) = S3_VIRTUAL_HOSTED_RE.captures(&host) {
+                (
+                    Provider::S3,
+                    (
+                        captures[1].to_owned(),
+                        path.trim_start_matches('/').to_owned(),
+                    ),
+                )
+            } else if S3_PATH_STYLE_RE.is_match(&host).unwrap_or(false) {
+                (Provider::S3, split_bucket(path)?)
+            } else if let Ok(Some(captures)) = GCS_VIRTUAL_HOSTED_RE.captures(&host) {
+                (
+                    Provider::Gcs,
+                    (
+                        captures[1].to_owned(),
+                        path.trim_start_matches('/').to_owned(),
+                    ),
+                )
+            } else if host == "storage.googleapis.com" || host == "storage.cloud.google.com" {
+                (Provider::Gcs, split_bucket(path)?)
+            } else if let Ok(Some(captures)) = AZURE_RE.captures(&host) {
+                (Provider::Azure(captures[1].to_owned()), split_bucket(path)?)
+            } else {
+                return None;
+            }
+        }
+        _ => return None,
+    };
+
+    // Bucket and container URLs don't reference an object
+    if key.is_empty() {
+        return None;
+    }
+
+    Some(StorageObject {
+        provider,
+        bucket,
+        key,
+    })
+}
+
+impl Provider {
+    /// Returns `true` for query parameters of a pre-signed URL or a SAS token.
+    fn is_signature_parameter(&self, key: &str) -> bool {
+        lazy_static! {
+            static ref S3_RE: Regex =
+                Regex::new(r"(?i)^(?:x-amz-.*|awsaccesskeyid|signature|expires)$").unwrap();
+            static ref GCS_RE: Regex =
+                Regex::new(r"(?i)^(?:x-goog-.*|googleaccessid|signature|expires)$").unwrap();
+            static ref AZURE_RE: Regex = Regex::new(
+                r"^(?:sv|ss|srt|sp|se|st|spr|sig|sr|si|sdd|skoid|sktid|skt|ske|sks|skv|saoid|suoid|scid|rscc|rscd|rsce|rscl|rsct)$"
+            )
+            .unwrap();
+        }
+        match self {
+            Provider::S3 => S3_RE.is_match(key),
+            Provider::Gcs => GCS_RE.is_match(key),
+            Provider::Azure(_) => AZURE_RE.is_match(key),
+        }
+        .unwrap_or(false)
+    }
+}
+
+/// Rewrites an object-storage URL to the canonical form selected by the `object_storage` option.
+///
+/// Returns `None` if `url_obj` doesn't reference an object.
+pub(crate) fn canonicalize_storage_url(
+    url_obj: &Url,
+    options: &Options,
+) -> Result<Option<String>, NormalizeUrlError> {
+    if options.object_storage == ObjectStorageOptions::None {
+        return Ok(None);
+    }
+
+    let Some(object) = parse_storage_object(url_obj) else {
+        return Ok(None);
+    };
+
+    let mut query_pairs = vec![];
+    for (key, value) in url_obj.query_pairs() {
+        if !object.provider.is_signature_parameter(&key) && is_query_parameter_kept(&key, options)?
+        {
+            query_pairs.push((key, value));
+        }
+    }
+    if options.sort_query_parameters {
+        query_pairs.sort_by(|a, b| a.0.cmp(&b.0));
+    }
+
+    // Keys are arbitrary bytes, which may not be valid UTF-8
+    let key = percent_encode(
+        &percent_decode_str(&object.key).collect::<Vec<_>>(),
+        KEY_ENCODE_SET,
+    )
+    .to_string();
+    let mut result = match (options.object_storage, object.provider) {
+        (ObjectStorageOptions::Native, Provider::S3) => format!("s3://{}/{}", object.bucket, key),
+        (ObjectStorageOptions::Native, Provider::Gcs) => format!("gs://{}/{}", object.bucket, key),
+        (ObjectStorageOptions::Native, Provider::Azure(account)) => {
+            format!("az://{}/{}/{}", account, object.bucket, key)
+        }
+        (_, Provider::S3) => format!("https://{}.s3.amazonaws.com/{}", object.bucket, key),
+        (_, Provider::Gcs) => format!("https://storage.googleapis.com/{}/{}", object.bucket, key),
+        (_, Provider::Azure(account)) => format!(
+            "https://{}.blob.core.windows.net/{}/{}",
+            account, object.bucket, key
+        ),
+    };
+
+    if !query_pairs.is_empty() {
+        result += "?";
+        result += &url::form_urlencoded::Serializer::new(String::new())
+            .extend_pairs(query_pairs)
+            .finish();
+    }
+
+    Ok(Some(result))
+}
@@ -0,0 +1,115 @@
+use fancy_regex::Regex;
+use lazy_static::lazy_static;
+use url::{Host, Position};
+
+use crate::{normalize_and_parse_url, parse_url_string, NormalizeUrlError, Options};
+
+/// Converts a URL to its SURT (Sort-friendly URI Reordering Transform) form, e.g.
+/// `https://www.example.com/path?b=2&a=1` → `com,example)/path?a=1&b=2`.
+///
+/// The URL is normalized with [`normalize_url`](crate::normalize_url) first, then the Heritrix and Internet Archive conventions are applied:
+/// - Host labels are reversed and joined with `,`, followed by the port, if any, and `)`. IP addresses aren't reversed.
+/// - A leading `www` label, optionally followed by digits (e.g. `www2`), is removed when `strip_www` is set.
+/// - With `surt_include_scheme`, the scheme is kept and the host is written the Heritrix way, e.g. `https://(com,example,:8080)/path`.
+/// - The username, password and fragment are removed.
+///
+/// URLs without a host, e.g. `mailto:` URLs and relative references, are returned normalized. Protocol-relative URLs,
+/// kept by `normalize_protocol(false)`, are read with `default_protocol`.
+///
+/// ```
+/// use normalize_url_rs::{to_surt, OptionsBuilder};
+///
+/// let options = OptionsBuilder::default().build().unwrap();
+/// let result = to_surt("https://www2.Example.com:8080/path/?b=2&a=1", &options);
+///
+/// assert_eq!(result.unwrap(), "com,example:8080)/path?a=1&b=2")
+/// ```
+pub fn to_surt(url: &str, options: &Options) -> Result<String, NormalizeUrlError> {
+    lazy_static! {
+        static ref WWW_RE: Regex = Regex::new(r"^www\d*$").unwrap();
+    }
+
+    let (normalized, url_obj) = normalize_and_parse_url(url, options)?;
+    let Some(url_obj) = url_obj else {
+        return Ok(normalized);
+    };
+    let Some(host) = url_obj.host() else {
+        return Ok(normalized);
+    };
+
+    let surt = match host {
+        Host::Domain(domain) => {
+            let mut labels = domain.split('.').rev().collect::<Vec<_>>();
+            if options.strip_www
+                && labels.len() > 2
+                && WWW_RE.is_match(labels[labels.len() - 1]).unwrap_or(false)
+            {
+                labels.pop();
+            }
+            labels.join(",")
+        }
+        _ => url_obj.host_str().unwrap().to_owned(),
+    };
+    let port = url_obj
+        .port()
+        .map(|port| format!(":{}", port))
+        .unwrap_or_default();
+
+    let surt = match options.surt_include_scheme {
+        true => format!("{}://({},{})", url_obj.scheme(), surt, port),
+        false => format!("{}{})", surt, port),
+    };
+    Ok(format!(
+        "{}{}",
+        surt,
+        &url_obj[Position::BeforePath..Position::AfterQuery]
+    ))
+}
+
+/// Converts a SURT back to a URL, e.g. `com,example)/path?a=1` → `http://example.com/path?a=1`.
+///
+/// SURTs without a scheme get `default_protocol`. A SURT prefix without `)`, e.g. `com,example,`, is read as a host.
+/// Removed `www` labels, credentials and fragments can't be restored, and the URL isn't normalized.
+///
+/// ```
+/// use normalize_url_rs::{from_surt, OptionsBuilder};
+///
+/// let options = OptionsBuilder::default().build().unwrap();
+/// let result = from_surt("https://(com,example,:8080)/path?a=1", &options);
+///
+/// assert_eq!(result.unwrap(), "https://example.com:8080/path?a=1")
+/// ```
+pub fn from_surt(surt: &str, options: &Options) -> Result<String, NormalizeUrlError> {
+    lazy_static! {
+        static ref SCHEME_RE: Regex = Regex::new(r"^([a-zA-Z][a-zA-Z\d+.-]*):\/\/\(").unwrap();
+        static ref PORT_RE: Regex = Regex::new(r",?:(\d+)$").unwrap();
+        static ref IP_RE: Regex = Regex::new(r"^(?:[\d.]+|\[[\da-fA-F:.]+\])$").unwrap();
+    }
+
+    let surt = surt.trim();
+    let (scheme, surt) = match SCHEME_RE.captures(surt).ok().flatten() {
+        Some(captures) => (captures[1].to_lowercase(), &surt[captures[0].len()..]),
+        None => (options.default_protocol.clone(), surt),
+    };
+    let (host, path) = surt.split_once(')').unwrap_or((surt, ""));
+
+    let (host, port) = match PORT_RE.captures(host).ok().flatten() {
+        Some(captures) => (
+            &host[..captures.get(0).unwrap().start()],
+            format!(":{}", &captures[1]),
+        ),
+        None => (host, String::new()),
+    };
+    let host = host.trim_end_matches(',');
+    let host = match IP_RE.is_match(host).unwrap_or(false) {
+        true => host.to_owned(),
+        false => host.split(',').rev().collect::<Vec<_>>().join("."),
+    };
+
+    let path = match path.starts_with('/') {
+        true => path.to_owned(),
+        false => format!("/{}", path),
+    };
+    let url_obj = parse_url_string(&format!("{}://{}{}{}", scheme, host, port, path))?;
+    Ok(url_obj.to_string())
+}
@@ -0,0 +1,168 @@
+use derive_builder::Builder;
+use fancy_regex::Regex;
+use lazy_static::lazy_static;
+use urlencoding::decode;
+
+use crate::{normalize_url, NormalizeUrlError, Options};
+
+#[derive(Debug, Clone)]
+/// Replaces path segments and query values matching `regex` with the `{name}` placeholder, see [`template_url`].
+pub struct Detector {
+    /// Name of the placeholder.
+    pub name: String,
+    /// Regex matched against the whole percent-decoded path segment or query value.
+    pub regex: Regex,
+}
+
+impl Detector {
+    /// Creates a detector.
+    pub fn new(name: &str, regex: Regex) -> Self {
+        Detector {
+            name: name.to_owned(),
+            regex,
+        }
+    }
+}
+
+/// Returns the built-in detectors, in the order they're tried.
+pub fn default_detectors() -> Vec<Detector> {
+    [
+        (
+            "uuid",
+            r"^[\da-fA-F]{8}-[\da-fA-F]{4}-[\da-fA-F]{4}-[\da-fA-F]{4}-[\da-fA-F]{12}$",
+        ),
+        (
+            "date",
+            r"^\d{4}-\d{2}-\d{2}(?:T\d{2}:\d{2}(?::\d{2}(?:\.\d+)?)?(?:Z|[+-]\d{2}:?\d{2})?)?$",
+        ),
+        ("email", r"^[^@\s\/]+@[^@\s\/]+\.[^@\s\/]+$"),
+        ("id", r"^\d+$"),
+        ("hash", r"^[\da-fA-F]{16,}$"),
+        (
+            "base64",
+            r"^(?=.*[A-Z])(?=.*[a-z])(?=.*\d)[A-Za-z\d+\/_-]{20,}={0,2}$",
+        ),
+        ("slug", r"^[a-z\d]+(?:-[a-z\d]+){2,}$"),
+    ]
+    .into_iter()
+    .map(|(name, regex)| Detector::new(name, Regex::new(regex).unwrap()))
+    .collect()
+}
+
+#[derive(Builder, Debug, Clone)]
+#[builder(setter(into))]
+/// URL templating options, see [`template_url`].
+pub struct TemplateOptions {
+    /// Detectors tried before the built-in ones.
+    ///
+    /// Default value: `[]`.
+    #[builder(default = "vec![]")]
+    pub custom_detectors: Vec<Detector>,
+    /// Built-in detectors: `uuid`, `date` (ISO 8601), `email`, `id` (digits), `hash` (16 or more hex digits),
+    /// `base64` (20 or more characters, mixing cases and digits) and `slug` (3 or more words joined with `-`).
+    ///
+    /// Default value: [`default_detectors`].
+    #[builder(default = "default_detectors()")]
+    pub detectors: Vec<Detector>,
+    /// Replace query values as well as path segments.
+    ///
+    /// Default value: `true`.
+    #[builder(default = "true")]
+    pub template_query_values: bool,
+}
+
+impl TemplateOptions {
+    /// Returns the `{name}` placeholder of the first detector matching `value`, if any.
+    fn placeholder(&self, value: &str) -> Option<String> {
+        let value = decode(value).map_or_else(|_| value.to_owned(), |value| value.into_owned());
+        self.custom_detectors
+            .iter()
+            .chain(&self.detectors)
+            .find(|detector| detector.regex.is_match(&value).unwrap_or(false))
+            .map(|detector| format!("{{{}}}", detector.name))
+    }
+}
+
+/// Normalizes a URL with [`normalize_url`], then replaces its path segments and query values matching a detector
+/// with a `{name}` placeholder, e.g. `/users/12345/orders/<uuid>` → `/users/{id}/orders/{uuid}`.
+///
+/// The result is a low-cardinality key, e.g. for metrics labels: the fragment is removed, and placeholders aren't
+/// percent-encoded, so it isn't meant to be parsed as a URL. The scheme of non-hierarchical URLs is kept, e.g.
+/// `mailto:me@example.com` → `mailto:{email}`.
+///
+/// ```
+/// use normalize_url_rs::{template_url, OptionsBuilder, TemplateOptionsBuilder};
+///
+/// let options = OptionsBuilder::default().build().unwrap();
+/// let template_options = TemplateOptionsBuilder::default().build().unwrap();
+/// let result = template_url(
+///     "https://example.com/users/12345/orders/9f1c2b7e-4d1a-4c3b-9e8f-0a1b2c3d4e5f?since=2024-01-31",
+///     &template_options,
+///     &options,
+/// );
+///
+/// assert_eq!(result.unwrap(), "https://example.com/users/{id}/orders/{uuid}?since={date}")
+/// ```
+pub fn template_url(
+    url: &str,
+    template_options: &TemplateOptions,
+    options: &Options,
+) -> Result<String, NormalizeUrlError> {
+    let normalized = normalize_url(url, options)?;
+    let url_string = normalized
+        .split_once('#')
+        .map_or(normalized.as_str(), |(url_string, _)| url_string);
+    let (url_string, query) = match url_string.split_once('?') {
+        Some((url_string, query)) => (url_string, Some(query)),
+        None => (url_string, None),
+    };
+
+    lazy_static! {
+        // A host followed by a port, e.g. `localhost:8080/a` with `strip_protocol`, isn't a scheme
+        static ref SCHEME_RE: Regex = Regex::new(r"^[a-zA-Z][a-zA-Z\d+\-.]*:(?!\d+(?:\/|$))").unwrap();
+    }
+
+    // The path starts after the authority of hierarchical URLs, or after the scheme of the others, e.g. `mailto:`
+    let path_start = match url_string.find("://") {
+        Some(index) => url_string[index + 3..]
+            .find('/')
+            .map_or(url_string.len(), |path_index| index + 3 + path_index),
+        None => SCHEME_RE
+            .find(url_string)
+            .map_err(Into::into)
+            .map_err(NormalizeUrlError::UnexpectedError)?
+            .map_or(0, |scheme| scheme.end()),
+    };
+    let (prefix, path) = url_string.split_at(path_start);
+
+    let mut result = prefix.to_owned();
+    result += &path
+        .split('/')
+        .map(|segment| {
+            template_options
+                .placeholder(segment)
+                .unwrap_or_else(|| segment.to_owned())
+        })
+        .collect::<Vec<_>>()
+        .join("/");
+
+    if let Some(query) = query {
+        result += "?";
+        result += &query
+            .split('&')
+            .map(|pair| match pair.split_once('=') {
+                Some((key, value)) if template_options.template_query_values => {
+                    let value = value.replace('+', " ");
+                    match template_options.placeholder(&value) {
+                        Some(placeholder) => format!("{}={}", key, placeholder),
+                        None => pair.to_owned(),
+                    }
+                }
+                _ => pair.to_owned(),
+            })
+            .collect::<Vec<_>>()
+            .join("&");
+    }
+
+    Ok(result)
+}
@@ -0,0 +1,73 @@
+use fancy_regex::Regex;
+use lazy_static::lazy_static;
+
+use crate::{normalize_url, NormalizeUrlError, Options};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Controls how URLs wrapped in a prefix scheme (`view-source:`, `blob:`, `jar:`, `feed:`) will be normalized.
+pub enum WrapperSchemeOptions {
+    /// The wrapper will be kept and the inner URL normalized: `VIEW-SOURCE:https://www.example.com/` → `view-source:https://example.com`.
+    Keep,
+    /// The wrapper will be removed and the inner URL normalized: `view-source:https://www.example.com/` → `https://example.com`.
+    ///
+    /// The entry of a `jar:` URL isn't part of the inner URL, so only the archive URL is returned.
+    Strip,
+}
+
+/// Normalizes a URL wrapped in prefix schemes, e.g. `view-source:https://example.com` or `jar:https://example.com/a.jar!/b.class`.
+///
+/// Nested wrappers are unwrapped in a loop, so deeply nested input can't overflow the stack.
+///
+/// Returns `None` if `url_string` isn't a wrapped URL.
+pub(crate) fn normalize_wrapped_url(
+    url_string: &str,
+    options: &Options,
+) -> Result<Option<String>, NormalizeUrlError> {
+    lazy_static! {
+        // The inner URL must be absolute or protocol-relative
+        static ref RE: Regex =
+            Regex::new(r"(?i)^(view-source|blob|jar|feed):(?=[a-z][a-z\d+.-]*:|\/\/)").unwrap();
+    }
+
+    let mut inner = url_string;
+    let mut wrappers = vec![];
+    let mut entries = vec![];
+    while let Some(captures) = RE
+        .captures(inner)
+        .map_err(Into::into)
+        .map_err(NormalizeUrlError::UnexpectedError)?
+    {
+        let wrapper = captures[1].to_lowercase();
+        inner = &inner[captures[0].len()..];
+        if wrapper == "jar" {
+            if let Some(index) = inner.rfind("!/") {
+                entries.push(&inner[index..]);
+                inner = &inner[..index];
+            }
+        }
+        wrappers.push(wrapper);
+    }
+    if wrappers.is_empty() {
+        return Ok(None);
+    }
+
+    if options.wrapper_schemes == WrapperSchemeOptions::Strip {
+        return normalize_url(inner, options).map(Some);
+    }
+
+    // The inner URL of a kept wrapper must keep its protocol
+    let inner_options = Options {
+        strip_protocol: false,
+        ..options.clone()
+    };
+    let inner = normalize_url(inner, &inner_options)?;
+
+    // Entries of nested `jar:` URLs follow the innermost URL first
+    entries.reverse();
+    Ok(Some(format!(
+        "{}:{}{}",
+        wrappers.join(":"),
+        inner,
+        entries.concat()
+    )))
+}